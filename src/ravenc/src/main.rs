@@ -1,10 +1,32 @@
+mod output;
+
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+
 use hex_color::HexColor;
 use ravenwm_core::ipc;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
+use crate::output::Output;
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "ravenc")]
 struct Args {
+    /// Prints results and errors as JSON, one object per line.
+    #[structopt(long, global = true)]
+    json: bool,
+
+    /// The X display of the ravenwm instance to talk to (e.g., `:1`).
+    #[structopt(long, global = true)]
+    display: Option<String>,
+
+    /// The socket of the ravenwm instance to talk to.
+    ///
+    /// Takes precedence over `--display`.
+    #[structopt(long, global = true, parse(from_os_str))]
+    socket: Option<PathBuf>,
+
     #[structopt(subcommand)]
     command: Command,
 }
@@ -13,32 +35,358 @@ struct Args {
 #[structopt(rename_all = "snake_case")]
 enum Command {
     Quit,
-    MoveWindow { x: u32, y: u32 },
-    CloseWindow,
-    BorderWidth { width_in_px: u32 },
-    BorderColor { color: HexColor },
+    MoveWindow {
+        x: u32,
+        y: u32,
+    },
+    CloseWindow {
+        /// Kills the client right away instead of asking the window to close.
+        #[structopt(long)]
+        force: bool,
+    },
+    BorderWidth {
+        width_in_px: u32,
+    },
+    BorderColor {
+        color: HexColor,
+    },
+    /// Queries the state of ravenwm.
+    Query {
+        #[structopt(subcommand)]
+        query: QueryCommand,
+    },
+    /// Reads commands from stdin, one per line, and sends them over a single connection.
+    ///
+    /// Arguments with spaces (e.g., chords) can be quoted like in a shell.
+    Batch,
+    /// Prints events as they happen.
+    ///
+    /// Subscribes to all events if no kinds are given.
+    Subscribe {
+        /// The kinds of events to subscribe to (window, focus, border, layout, quit, config, workspace,
+        /// monitor).
+        events: Vec<ipc::EventKind>,
+    },
+    /// Binds a key chord (e.g., "super + alt + w") to a command.
+    ///
+    /// The command is either another ravenc command (e.g., `close_window`) or
+    /// `spawn` followed by a shell command.
+    #[structopt(setting = AppSettings::TrailingVarArg)]
+    Bind {
+        chord: String,
+        #[structopt(required = true, allow_hyphen_values = true)]
+        action: Vec<String>,
+    },
+    /// Removes the binding of a key chord.
+    Unbind {
+        chord: String,
+    },
+    /// Reloads the configuration file.
+    Reload,
+    /// Sets the share of the screen taken up by the master area (e.g., `0.6`,
+    /// `+0.05` or `-0.05`).
+    #[structopt(setting = AppSettings::AllowNegativeNumbers)]
+    MasterRatio {
+        #[structopt(parse(try_from_str = parse_ratio))]
+        ratio: ipc::Adjustment<f32>,
+    },
+    /// Sets the number of windows in the master area (e.g., `2`, `+1` or `-1`).
+    #[structopt(setting = AppSettings::AllowNegativeNumbers)]
+    MasterCount {
+        count: ipc::Adjustment<i32>,
+    },
+    /// Switches layouts (master_stack, bsp, monocle, grid, columns, rows or stacking).
+    ///
+    /// `tiling` switches from stacking back to the last tiled layout.
+    Layout {
+        name: String,
+    },
+    /// Changes the split tree of the bsp layout.
+    Bsp {
+        #[structopt(subcommand)]
+        command: BspCommand,
+    },
+    /// Toggles whether the focused window floats above the tiled windows.
+    ToggleFloating,
+    /// Shows the workspace with the given number, counting from 1.
+    Workspace {
+        number: u32,
+    },
+    /// Moves the focused window to the workspace with the given number, counting from 1.
+    SendTo {
+        number: u32,
+    },
+    /// Focuses the monitor with the given number, counting from 1 on the left.
+    FocusMonitor {
+        number: u32,
+    },
+    /// Moves the focused window to the monitor with the given number, counting from 1 on the left.
+    SendToMonitor {
+        number: u32,
+    },
+    /// Restarts ravenwm in place, keeping the windows and workspaces.
+    Restart,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "snake_case")]
+enum QueryCommand {
+    /// Lists all managed clients.
+    Clients,
+    /// Shows the focused client.
+    Focused,
+    /// Shows the current layout.
+    Layout,
+    /// Shows the current configuration.
+    Config,
+    /// Lists all workspaces.
+    Workspaces,
+    /// Lists all monitors.
+    Monitors,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "snake_case")]
+enum BspCommand {
+    /// Rotates the split containing the focused window clockwise (90, 180 or 270 degrees).
+    Rotate {
+        #[structopt(default_value = "90")]
+        degrees: u16,
+    },
+    /// Mirrors the split containing the focused window (horizontal or vertical).
+    Flip { direction: ipc::Flip },
+    /// Gives every window the same area.
+    Balance,
+    /// Sets the share of the split containing the focused window taken up by its first half
+    /// (e.g., `0.6`, `+0.05` or `-0.05`).
+    #[structopt(setting = AppSettings::AllowNegativeNumbers)]
+    Ratio {
+        #[structopt(parse(try_from_str = parse_ratio))]
+        ratio: ipc::Adjustment<f32>,
+    },
+}
+
+impl Command {
+    fn into_message(self) -> Result<ipc::Message, String> {
+        match self {
+            Command::Quit => Ok(ipc::Message::Quit),
+            Command::MoveWindow { x, y } => Ok(ipc::Message::MoveWindow { x, y }),
+            Command::CloseWindow { force: false } => Ok(ipc::Message::CloseWindow),
+            Command::CloseWindow { force: true } => Ok(ipc::Message::KillWindow),
+            Command::BorderWidth { width_in_px } => {
+                Ok(ipc::Message::SetBorderWidth { width: width_in_px })
+            }
+            Command::BorderColor { color } => Ok(ipc::Message::SetBorderColor { color }),
+            Command::Query { query } => Ok(ipc::Message::Query(match query {
+                QueryCommand::Clients => ipc::Query::Clients,
+                QueryCommand::Focused => ipc::Query::Focused,
+                QueryCommand::Layout => ipc::Query::Layout,
+                QueryCommand::Config => ipc::Query::Config,
+                QueryCommand::Workspaces => ipc::Query::Workspaces,
+                QueryCommand::Monitors => ipc::Query::Monitors,
+            })),
+            Command::Bind { chord, action } => Ok(ipc::Message::Bind {
+                chord,
+                action: parse_action(action)?,
+            }),
+            Command::Unbind { chord } => Ok(ipc::Message::Unbind { chord }),
+            Command::Reload => Ok(ipc::Message::Reload),
+            Command::MasterRatio { ratio } => Ok(ipc::Message::SetMasterRatio { ratio }),
+            Command::MasterCount { count } => Ok(ipc::Message::SetMasterCount { count }),
+            Command::Layout { name } => Ok(ipc::Message::SetLayout { name }),
+            Command::Bsp { command } => Ok(ipc::Message::Bsp(match command {
+                BspCommand::Rotate { degrees } => ipc::BspCommand::Rotate { degrees },
+                BspCommand::Flip { direction } => ipc::BspCommand::Flip(direction),
+                BspCommand::Balance => ipc::BspCommand::Balance,
+                BspCommand::Ratio { ratio } => ipc::BspCommand::SetRatio(ratio),
+            })),
+            Command::ToggleFloating => Ok(ipc::Message::ToggleFloating),
+            Command::Workspace { number } => Ok(ipc::Message::Workspace { number }),
+            Command::SendTo { number } => Ok(ipc::Message::SendTo { number }),
+            Command::FocusMonitor { number } => Ok(ipc::Message::FocusMonitor { number }),
+            Command::SendToMonitor { number } => Ok(ipc::Message::SendToMonitor { number }),
+            Command::Restart => Ok(ipc::Message::Restart),
+            Command::Batch => Err("batch cannot be sent as a single message".to_string()),
+            Command::Subscribe { .. } => {
+                Err("subscribe cannot be sent as a single message".to_string())
+            }
+        }
+    }
+}
+
+/// Parses a ratio, which must be a finite number.
+fn parse_ratio(s: &str) -> Result<ipc::Adjustment<f32>, String> {
+    let ratio: ipc::Adjustment<f32> = s.parse()?;
+
+    if !ratio.is_finite() {
+        return Err(format!("Invalid value '{}'", s));
+    }
+
+    Ok(ratio)
+}
+
+/// Parses the action of a key binding from the given arguments.
+fn parse_action(args: Vec<String>) -> Result<ipc::Action, String> {
+    if args.first().map(String::as_str) == Some("spawn") {
+        if args.len() < 2 {
+            return Err("spawn requires a command".to_string());
+        }
+
+        return Ok(ipc::Action::Spawn(args[1..].join(" ")));
+    }
+
+    let args = Args::from_iter_safe(std::iter::once("ravenc".to_string()).chain(args))
+        .map_err(|err| err.message)?;
+
+    Ok(ipc::Action::Message(Box::new(args.command.into_message()?)))
 }
 
 #[paw::main]
 fn main(args: Args) {
-    let socket = ipc::SocketPath::new();
-    let mut ipc_client = ipc::Client::connect(&socket);
+    let output = if args.json {
+        Output::Json
+    } else {
+        Output::Human
+    };
+
+    let socket = match (args.socket, args.display) {
+        (Some(socket), _) => Ok(ipc::SocketPath::from(socket)),
+        (None, Some(display)) => ipc::SocketPath::for_display(&display),
+        (None, None) => ipc::SocketPath::new(),
+    };
+
+    let ipc_client = match socket.and_then(|socket| ipc::Client::connect(&socket)) {
+        Ok(ipc_client) => ipc_client,
+        Err(err) => {
+            output.error(err);
+            std::process::exit(1);
+        }
+    };
+
+    let succeeded = match args.command {
+        Command::Subscribe { events } => subscribe(ipc_client, events, output),
+        Command::Batch => match read_batch() {
+            Ok(messages) => send(ipc_client, &messages, output),
+            Err(err) => {
+                output.error(err);
+                false
+            }
+        },
+        command => match command.into_message() {
+            Ok(message) => send(ipc_client, &[message], output),
+            Err(err) => {
+                output.error(err);
+                false
+            }
+        },
+    };
+
+    if !succeeded {
+        std::process::exit(1);
+    }
+}
+
+/// Sends the given messages and prints their replies.
+///
+/// Returns whether all of the messages succeeded.
+fn send(mut ipc_client: ipc::Client, messages: &[ipc::Message], output: Output) -> bool {
+    match ipc_client.send_batch(messages) {
+        Ok(replies) => {
+            let mut succeeded = true;
 
-    match args.command {
-        Command::Quit => {
-            ipc_client.send(&ipc::Message::Quit);
+            for reply in replies {
+                succeeded &= output.reply(reply);
+            }
+
+            succeeded
+        }
+        Err(err) => {
+            output.error(err);
+            false
         }
-        Command::MoveWindow { x, y } => {
-            ipc_client.send(&ipc::Message::MoveWindow { x, y });
+    }
+}
+
+/// Subscribes to the given kinds of events and prints them until `ravenwm` quits.
+///
+/// Returns whether the subscription ended without an error.
+fn subscribe(ipc_client: ipc::Client, events: Vec<ipc::EventKind>, output: Output) -> bool {
+    let subscription = match ipc_client.subscribe(events) {
+        Ok(subscription) => subscription,
+        Err(err) => {
+            output.error(err);
+            return false;
         }
-        Command::CloseWindow => {
-            ipc_client.send(&ipc::Message::CloseWindow);
+    };
+
+    for event in subscription {
+        match event {
+            Ok(event) => output.event(event),
+            Err(err) => {
+                output.error(err);
+                return false;
+            }
         }
-        Command::BorderWidth { width_in_px } => {
-            ipc_client.send(&ipc::Message::SetBorderWidth { width: width_in_px });
+    }
+
+    true
+}
+
+/// Reads a batch of commands from stdin.
+fn read_batch() -> Result<Vec<ipc::Message>, String> {
+    let stdin = io::stdin();
+
+    let mut messages = Vec::new();
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|err| format!("Failed to read from stdin: {}", err))?;
+
+        if let Some(message) = parse_batch_line(&line)? {
+            messages.push(message);
         }
-        Command::BorderColor { color } => {
-            ipc_client.send(&ipc::Message::SetBorderColor { color });
+    }
+
+    Ok(messages)
+}
+
+/// Parses a line of a batch, which is split into arguments like a shell
+/// would, so that quoted arguments can contain spaces.
+///
+/// Returns `None` for blank lines.
+fn parse_batch_line(line: &str) -> Result<Option<ipc::Message>, String> {
+    let words =
+        shell_words::split(line).map_err(|err| format!("Invalid line '{}': {}", line, err))?;
+
+    if words.is_empty() {
+        return Ok(None);
+    }
+
+    let args = Args::from_iter_safe(std::iter::once("ravenc".to_string()).chain(words))
+        .map_err(|err| err.message)?;
+
+    args.command.into_message().map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_line_with_quoted_chord() {
+        let message = parse_batch_line(r#"bind "super + q" spawn "xterm -e 'top'""#).unwrap();
+
+        match message {
+            Some(ipc::Message::Bind {
+                chord,
+                action: ipc::Action::Spawn(command),
+            }) => {
+                assert_eq!(chord, "super + q");
+                assert_eq!(command, "xterm -e 'top'");
+            }
+            message => panic!("Unexpected message: {:?}", message),
         }
+
+        assert!(parse_batch_line("   ").unwrap().is_none());
+        assert!(parse_batch_line("bind \"super + q").is_err());
     }
 }
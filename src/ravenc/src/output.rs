@@ -0,0 +1,232 @@
+use ravenwm_core::ipc;
+use serde_json::{json, Value};
+
+/// The format `ravenc` prints results in.
+#[derive(Debug, Clone, Copy)]
+pub enum Output {
+    /// Human-readable text and tables.
+    Human,
+
+    /// One JSON object per line.
+    Json,
+}
+
+impl Output {
+    /// Prints the given [`ipc::Reply`].
+    ///
+    /// Returns whether the reply indicates success.
+    pub fn reply(&self, reply: ipc::Reply) -> bool {
+        match self {
+            Self::Human => match reply {
+                ipc::Reply::Ok => true,
+                ipc::Reply::Error { reason } => {
+                    eprintln!("ravenc: {}", reason);
+                    false
+                }
+                ipc::Reply::Data(data) => {
+                    print_data(data);
+                    true
+                }
+            },
+            Self::Json => {
+                let (value, is_ok) = match reply {
+                    ipc::Reply::Ok => (json!({ "status": "ok" }), true),
+                    ipc::Reply::Error { reason } => {
+                        (json!({ "status": "error", "reason": reason }), false)
+                    }
+                    ipc::Reply::Data(data) => {
+                        (json!({ "status": "ok", "data": data_to_json(data) }), true)
+                    }
+                };
+
+                println!("{}", value);
+
+                is_ok
+            }
+        }
+    }
+
+    /// Prints an error that occurred within `ravenc` itself.
+    pub fn error(&self, reason: impl std::fmt::Display) {
+        match self {
+            Self::Human => eprintln!("ravenc: {}", reason),
+            Self::Json => println!(
+                "{}",
+                json!({ "status": "error", "reason": reason.to_string() })
+            ),
+        }
+    }
+
+    /// Prints the given [`ipc::Event`].
+    pub fn event(&self, event: ipc::Event) {
+        match self {
+            Self::Human => match event {
+                ipc::Event::WindowMapped { window } => println!("window_mapped 0x{:08x}", window),
+                ipc::Event::WindowUnmapped { window } => {
+                    println!("window_unmapped 0x{:08x}", window)
+                }
+                ipc::Event::FocusChanged {
+                    window: Some(window),
+                } => println!("focus_changed 0x{:08x}", window),
+                ipc::Event::FocusChanged { window: None } => println!("focus_changed none"),
+                ipc::Event::BorderChanged { width, color } => {
+                    println!("border_changed {} {}", width, color)
+                }
+                ipc::Event::LayoutChanged { layout } => println!("layout_changed {}", layout),
+                ipc::Event::Quit => println!("quit"),
+                ipc::Event::ConfigReloaded { error: None } => println!("config_reloaded"),
+                ipc::Event::ConfigReloaded { error: Some(error) } => {
+                    println!("config_reloaded error: {}", error)
+                }
+                ipc::Event::WorkspaceChanged { workspace, name } => {
+                    println!("workspace_changed {} {}", workspace, name)
+                }
+                ipc::Event::MonitorFocused { monitor, name } => {
+                    println!("monitor_focused {} {}", monitor, name)
+                }
+            },
+            Self::Json => println!("{}", event_to_json(event)),
+        }
+    }
+}
+
+fn data_to_json(data: ipc::Data) -> Value {
+    match data {
+        ipc::Data::Clients(clients) => json!(clients),
+        ipc::Data::Focused(client) => json!(client),
+        ipc::Data::Layout { name } => json!({ "name": name }),
+        ipc::Data::Config(config) => json!(config),
+        ipc::Data::Workspaces(workspaces) => json!(workspaces),
+        ipc::Data::Monitors(monitors) => json!(monitors),
+    }
+}
+
+fn event_to_json(event: ipc::Event) -> Value {
+    match event {
+        ipc::Event::WindowMapped { window } => {
+            json!({ "event": "window_mapped", "window": window })
+        }
+        ipc::Event::WindowUnmapped { window } => {
+            json!({ "event": "window_unmapped", "window": window })
+        }
+        ipc::Event::FocusChanged { window } => {
+            json!({ "event": "focus_changed", "window": window })
+        }
+        ipc::Event::BorderChanged { width, color } => {
+            json!({ "event": "border_changed", "width": width, "color": color })
+        }
+        ipc::Event::LayoutChanged { layout } => {
+            json!({ "event": "layout_changed", "layout": layout })
+        }
+        ipc::Event::Quit => json!({ "event": "quit" }),
+        ipc::Event::ConfigReloaded { error } => {
+            json!({ "event": "config_reloaded", "error": error })
+        }
+        ipc::Event::WorkspaceChanged { workspace, name } => {
+            json!({ "event": "workspace_changed", "workspace": workspace, "name": name })
+        }
+        ipc::Event::MonitorFocused { monitor, name } => {
+            json!({ "event": "monitor_focused", "monitor": monitor, "name": name })
+        }
+    }
+}
+
+fn print_data(data: ipc::Data) {
+    match data {
+        ipc::Data::Clients(clients) => print_clients(&clients),
+        ipc::Data::Focused(Some(client)) => print_clients(&[client]),
+        ipc::Data::Focused(None) => {}
+        ipc::Data::Layout { name } => println!("{}", name),
+        ipc::Data::Config(config) => print_table(
+            &["SETTING", "VALUE"],
+            vec![
+                vec!["border_width".into(), config.border_width.to_string()],
+                vec!["border_color".into(), config.border_color.to_string()],
+                vec!["gap_width".into(), config.gap_width.to_string()],
+            ],
+        ),
+        ipc::Data::Workspaces(workspaces) => print_table(
+            &["", "WORKSPACE", "NAME", "LAYOUT", "CLIENTS", "MONITOR"],
+            workspaces
+                .into_iter()
+                .map(|workspace| {
+                    vec![
+                        if workspace.focused { "*" } else { "" }.to_string(),
+                        workspace.number.to_string(),
+                        workspace.name,
+                        workspace.layout,
+                        workspace.clients.to_string(),
+                        workspace
+                            .monitor
+                            .map(|monitor| monitor.to_string())
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        ),
+        ipc::Data::Monitors(monitors) => print_table(
+            &["", "MONITOR", "NAME", "GEOMETRY", "WORKSPACE"],
+            monitors
+                .into_iter()
+                .map(|monitor| {
+                    vec![
+                        if monitor.focused { "*" } else { "" }.to_string(),
+                        monitor.number.to_string(),
+                        monitor.name,
+                        format!(
+                            "{}x{}+{}+{}",
+                            monitor.width, monitor.height, monitor.x, monitor.y
+                        ),
+                        monitor.workspace.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn print_clients(clients: &[ipc::ClientInfo]) {
+    let rows = clients
+        .iter()
+        .map(|client| {
+            let geometry = client.geometry;
+
+            vec![
+                if client.focused { "*" } else { "" }.to_string(),
+                format!("0x{:08x}", client.window),
+                format!(
+                    "{}x{}+{}+{}",
+                    geometry.width, geometry.height, geometry.x, geometry.y
+                ),
+                client.class.clone().unwrap_or_else(|| "-".into()),
+                client.title.clone().unwrap_or_else(|| "-".into()),
+            ]
+        })
+        .collect();
+
+    print_table(&["", "WINDOW", "GEOMETRY", "CLASS", "TITLE"], rows);
+}
+
+/// Prints the given rows as a table with aligned columns.
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+
+    for row in std::iter::once(headers).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        println!("{}", line.trim_end());
+    }
+}
@@ -0,0 +1,55 @@
+use xcb::x;
+
+macro_rules! atoms {
+    ($($field:ident => $name:literal,)*) => {
+        /// The X atoms used by `ravenwm`.
+        #[derive(Debug, Clone, Copy)]
+        pub struct Atoms {
+            $(
+                #[doc = concat!("The `", $name, "` atom.")]
+                pub $field: x::Atom,
+            )*
+        }
+
+        impl Atoms {
+            /// Interns all of the [`Atoms`].
+            pub fn intern(conn: &xcb::Connection) -> xcb::Result<Self> {
+                $(
+                    let $field = conn.send_request(&x::InternAtom {
+                        only_if_exists: false,
+                        name: $name.as_bytes(),
+                    });
+                )*
+
+                Ok(Self {
+                    $(
+                        $field: conn.wait_for_reply($field)?.atom(),
+                    )*
+                })
+            }
+        }
+    };
+}
+
+atoms! {
+    wm_protocols => "WM_PROTOCOLS",
+    wm_delete_window => "WM_DELETE_WINDOW",
+    wm_take_focus => "WM_TAKE_FOCUS",
+    utf8_string => "UTF8_STRING",
+    net_wm_name => "_NET_WM_NAME",
+    net_supported => "_NET_SUPPORTED",
+    net_supporting_wm_check => "_NET_SUPPORTING_WM_CHECK",
+    net_client_list => "_NET_CLIENT_LIST",
+    net_client_list_stacking => "_NET_CLIENT_LIST_STACKING",
+    net_active_window => "_NET_ACTIVE_WINDOW",
+    net_number_of_desktops => "_NET_NUMBER_OF_DESKTOPS",
+    net_desktop_names => "_NET_DESKTOP_NAMES",
+    net_current_desktop => "_NET_CURRENT_DESKTOP",
+    net_desktop_geometry => "_NET_DESKTOP_GEOMETRY",
+    net_desktop_viewport => "_NET_DESKTOP_VIEWPORT",
+    net_wm_desktop => "_NET_WM_DESKTOP",
+    net_close_window => "_NET_CLOSE_WINDOW",
+    net_wm_moveresize => "_NET_WM_MOVERESIZE",
+    net_wm_state => "_NET_WM_STATE",
+    net_wm_state_fullscreen => "_NET_WM_STATE_FULLSCREEN",
+}
@@ -0,0 +1,133 @@
+use xcb::x;
+
+use crate::geometry::Rectangle;
+
+/// An X client.
+#[derive(Debug)]
+pub struct XClient {
+    window: x::Window,
+    floating_rect: Rectangle,
+    is_floating: bool,
+    is_fullscreen: bool,
+    is_mapped: bool,
+    expected_unmaps: u32,
+    original_geometry: Rectangle,
+    original_border_width: u16,
+}
+
+impl XClient {
+    /// Creates a new [`XClient`] for the given window, with the given area
+    /// for when it is floating.
+    pub fn new(window: x::Window, floating_rect: Rectangle) -> Self {
+        Self {
+            window,
+            floating_rect,
+            is_floating: false,
+            is_fullscreen: false,
+            is_mapped: false,
+            expected_unmaps: 0,
+            original_geometry: floating_rect,
+            original_border_width: 0,
+        }
+    }
+
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    /// Returns whether this client floats above the tiled clients.
+    pub fn is_floating(&self) -> bool {
+        self.is_floating
+    }
+
+    /// Sets whether this client floats above the tiled clients.
+    pub fn set_floating(&mut self, is_floating: bool) {
+        self.is_floating = is_floating;
+    }
+
+    /// Returns whether this client covers its whole monitor.
+    pub fn is_fullscreen(&self) -> bool {
+        self.is_fullscreen
+    }
+
+    /// Sets whether this client covers its whole monitor.
+    pub fn set_fullscreen(&mut self, is_fullscreen: bool) {
+        self.is_fullscreen = is_fullscreen;
+    }
+
+    /// Returns whether the window manager mapped this client's window.
+    pub fn is_mapped(&self) -> bool {
+        self.is_mapped
+    }
+
+    /// Remembers that the window manager mapped or unmapped this client's
+    /// window.
+    ///
+    /// Unmapping it makes the resulting `UnmapNotify` expected.
+    pub fn set_mapped(&mut self, is_mapped: bool) {
+        if self.is_mapped && !is_mapped {
+            self.expected_unmaps += 1;
+        }
+
+        self.is_mapped = is_mapped;
+    }
+
+    /// Returns whether an `UnmapNotify` for this client's window was caused
+    /// by the window manager rather than the client, consuming it.
+    pub fn take_expected_unmap(&mut self) -> bool {
+        if self.expected_unmaps > 0 {
+            self.expected_unmaps -= 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the geometry, without the border, and the border width the
+    /// window had before it was managed.
+    pub fn original_geometry(&self) -> (Rectangle, u16) {
+        (self.original_geometry, self.original_border_width)
+    }
+
+    /// Sets the geometry, without the border, and the border width the window
+    /// had before it was managed.
+    pub fn set_original_geometry(&mut self, geometry: Rectangle, border_width: u16) {
+        self.original_geometry = geometry;
+        self.original_border_width = border_width;
+    }
+
+    /// Returns the area of this client when it is floating, including its
+    /// border.
+    pub fn floating_rect(&self) -> Rectangle {
+        self.floating_rect
+    }
+
+    /// Sets the area of this client when it is floating, including its
+    /// border.
+    pub fn set_floating_rect(&mut self, floating_rect: Rectangle) {
+        self.floating_rect = floating_rect;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xcb::XidNew;
+
+    use super::*;
+
+    #[test]
+    fn only_unmapping_a_mapped_window_is_expected() {
+        let window = unsafe { x::Window::new(1) };
+        let mut client = XClient::new(window, Rectangle::new(0, 0, 100, 100));
+
+        client.set_mapped(false);
+        assert!(!client.take_expected_unmap());
+
+        client.set_mapped(true);
+        client.set_mapped(false);
+        client.set_mapped(false);
+        assert!(client.take_expected_unmap());
+        assert!(!client.take_expected_unmap());
+    }
+}
@@ -0,0 +1,517 @@
+use std::os::unix::prelude::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs, io};
+
+use hex_color::HexColor;
+use nix::errno::Errno;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+use ravenwm_core::ipc;
+use serde::Deserialize;
+
+use crate::keyboard::Chord;
+use crate::layout::{self, Placement};
+use crate::plumage::Color;
+
+/// The configuration of `ravenwm`, read from `ravenwm.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The width of the window borders, in pixels.
+    pub border_width: u32,
+
+    /// The color of the window borders.
+    pub border_color: HexColor,
+
+    /// The width of the gaps around windows, in pixels.
+    pub gap_width: u32,
+
+    /// The name of the layout windows are arranged in.
+    #[serde(deserialize_with = "deserialize_layout")]
+    pub layout: String,
+
+    /// How new windows are placed in the stacking layout.
+    pub placement: Placement,
+
+    /// The number of windows in the master area of the tiling layout.
+    pub master_count: usize,
+
+    /// The share of the screen taken up by the master area of the tiling
+    /// layout.
+    #[serde(deserialize_with = "deserialize_ratio")]
+    pub master_ratio: f32,
+
+    /// How long to wait for a window to close after asking it to, in
+    /// milliseconds, before killing its client. `0` waits forever.
+    pub close_timeout: u64,
+
+    /// The names of the workspaces, which also sets how many there are.
+    #[serde(deserialize_with = "deserialize_workspaces")]
+    pub workspaces: Vec<String>,
+
+    /// The key bindings.
+    pub bindings: Vec<Binding>,
+
+    /// The rules applied to new windows.
+    pub rules: Vec<Rule>,
+}
+
+impl Config {
+    /// Loads the configuration from the config file.
+    ///
+    /// Returns the default configuration if there is no config file.
+    pub fn load() -> Result<Self, ConfigError> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the configuration from the given file.
+    ///
+    /// Returns the default configuration if the file does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(ConfigError::Io(path.to_path_buf(), err)),
+        };
+
+        toml::from_str(&contents).map_err(|err| ConfigError::Invalid(path.to_path_buf(), err))
+    }
+
+    /// Returns the path to the config file.
+    ///
+    /// The config file lives at `$XDG_CONFIG_HOME/ravenwm/ravenwm.toml`, with
+    /// `$XDG_CONFIG_HOME` defaulting to `~/.config`.
+    pub fn path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|config_home| !config_home.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join("ravenwm").join("ravenwm.toml"))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            border_width: 0,
+            border_color: HexColor::new(
+                Color::MIDNIGHT_BLUE.r,
+                Color::MIDNIGHT_BLUE.g,
+                Color::MIDNIGHT_BLUE.b,
+            ),
+            gap_width: 16,
+            layout: "master_stack".to_string(),
+            placement: Placement::default(),
+            master_count: 1,
+            master_ratio: 0.5,
+            close_timeout: 5000,
+            workspaces: (1..=9).map(|number| number.to_string()).collect(),
+            bindings: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Deserializes the name of a layout, rejecting unknown layouts.
+fn deserialize_layout<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+
+    if layout::is_known(&name) {
+        Ok(name)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "unknown layout '{}'",
+            name
+        )))
+    }
+}
+
+/// Deserializes a ratio, rejecting infinity and NaN.
+fn deserialize_ratio<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let ratio = f32::deserialize(deserializer)?;
+
+    if ratio.is_finite() {
+        Ok(ratio)
+    } else {
+        Err(serde::de::Error::custom(
+            "the ratio must be a finite number",
+        ))
+    }
+}
+
+/// Deserializes the names of the workspaces, rejecting an empty list.
+fn deserialize_workspaces<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let names = Vec::<String>::deserialize(deserializer)?;
+
+    if names.is_empty() {
+        Err(serde::de::Error::custom(
+            "there must be at least one workspace",
+        ))
+    } else {
+        Ok(names)
+    }
+}
+
+/// A key chord bound to an action.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "RawBinding")]
+pub struct Binding {
+    pub chord: Chord,
+    pub action: ipc::Action,
+}
+
+/// A [`Binding`] as it is written in the config file, with exactly one of
+/// `message` or `spawn` set.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBinding {
+    chord: String,
+    message: Option<ipc::Message>,
+    spawn: Option<String>,
+}
+
+impl TryFrom<RawBinding> for Binding {
+    type Error = String;
+
+    fn try_from(binding: RawBinding) -> Result<Self, Self::Error> {
+        let chord = binding.chord.parse()?;
+
+        let action = match (binding.message, binding.spawn) {
+            (Some(message), None) => ipc::Action::Message(Box::new(message)),
+            (None, Some(command)) => ipc::Action::Spawn(command),
+            _ => {
+                return Err(format!(
+                    "Binding '{}' needs exactly one of `message` or `spawn`",
+                    binding.chord
+                ))
+            }
+        };
+
+        Ok(Self { chord, action })
+    }
+}
+
+/// A rule that changes how matching windows are handled.
+///
+/// A window matches if all of the given properties match. Later rules take
+/// precedence over earlier ones.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// The class of the window, from `WM_CLASS`.
+    pub class: Option<String>,
+
+    /// The instance of the window, from `WM_CLASS`.
+    pub instance: Option<String>,
+
+    /// A part of the title of the window.
+    pub title: Option<String>,
+
+    /// Whether the window is managed by `ravenwm`.
+    pub manage: Option<bool>,
+
+    /// Whether the window is focused when it is mapped.
+    pub focus: Option<bool>,
+}
+
+impl Rule {
+    /// Returns whether the given client matches this rule.
+    pub fn matches(&self, client: &ipc::ClientInfo) -> bool {
+        let matches = |pattern: &Option<String>, value: &Option<String>| match pattern {
+            Some(pattern) => value.as_ref() == Some(pattern),
+            None => true,
+        };
+
+        let title_matches = match &self.title {
+            Some(title) => client
+                .title
+                .as_ref()
+                .is_some_and(|client_title| client_title.contains(title.as_str())),
+            None => true,
+        };
+
+        matches(&self.class, &client.class)
+            && matches(&self.instance, &client.instance)
+            && title_matches
+    }
+}
+
+/// The combined effect of all [`Rule`]s that match a window.
+#[derive(Debug, Clone, Copy)]
+pub struct RuleEffects {
+    pub manage: bool,
+    pub focus: bool,
+}
+
+impl RuleEffects {
+    /// Returns the combined effect of all of the given rules that match the
+    /// given client.
+    pub fn for_client(rules: &[Rule], client: &ipc::ClientInfo) -> Self {
+        let mut effects = Self::default();
+
+        for rule in rules.iter().filter(|rule| rule.matches(client)) {
+            effects.manage = rule.manage.unwrap_or(effects.manage);
+            effects.focus = rule.focus.unwrap_or(effects.focus);
+        }
+
+        effects
+    }
+}
+
+impl Default for RuleEffects {
+    fn default() -> Self {
+        Self {
+            manage: true,
+            focus: true,
+        }
+    }
+}
+
+/// Watches the config file for changes.
+pub struct ConfigWatcher {
+    inotify: Inotify,
+    path: PathBuf,
+    watch: WatchDescriptor,
+    watched_directory: PathBuf,
+}
+
+impl ConfigWatcher {
+    /// Starts watching the config file at the given path.
+    ///
+    /// The directory containing the file is watched rather than the file
+    /// itself, so that changes are still noticed after an editor replaces it.
+    /// If the directory does not exist yet, its nearest existing ancestor is
+    /// watched until it is created.
+    pub fn new(path: &Path) -> nix::Result<Self> {
+        path.file_name().ok_or(Errno::EINVAL)?;
+
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+        let directory = Self::nearest_directory(path).ok_or(Errno::ENOENT)?;
+        let watch = Self::add_watch(inotify, path, &directory)?;
+
+        Ok(Self {
+            inotify,
+            path: path.to_path_buf(),
+            watch,
+            watched_directory: directory,
+        })
+    }
+
+    /// Returns the closest directory to the given config file that exists.
+    fn nearest_directory(path: &Path) -> Option<PathBuf> {
+        path.ancestors()
+            .skip(1)
+            .find(|ancestor| ancestor.is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Watches the given directory for changes to the config file at the
+    /// given path, or for the creation of the directories leading to it.
+    fn add_watch(inotify: Inotify, path: &Path, directory: &Path) -> nix::Result<WatchDescriptor> {
+        let flags = if Some(directory) == path.parent() {
+            AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO
+        } else {
+            AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO | AddWatchFlags::IN_ONLYDIR
+        };
+
+        inotify.add_watch(directory, flags)
+    }
+
+    /// Returns whether the config file changed since the last call.
+    pub fn has_changed(&mut self) -> bool {
+        let file_name = self.path.file_name();
+        let mut has_changed = false;
+        let mut is_watching_parent = Some(self.watched_directory.as_path()) == self.path.parent();
+        let mut has_directory_changed = false;
+
+        loop {
+            match self.inotify.read_events() {
+                Ok(events) => {
+                    for event in events.iter().filter(|event| event.wd == self.watch) {
+                        // The watch is removed along with its directory.
+                        if !is_watching_parent || event.mask.contains(AddWatchFlags::IN_IGNORED) {
+                            has_directory_changed = true;
+                        } else {
+                            has_changed |= event.name.as_deref() == file_name;
+                        }
+                    }
+                }
+                Err(Errno::EAGAIN) => break,
+                Err(Errno::EINTR) => {}
+                Err(err) => {
+                    println!("Failed to read config file changes: {}", err);
+                    break;
+                }
+            }
+        }
+
+        if has_directory_changed {
+            let was_watching_parent = is_watching_parent;
+            self.rewatch();
+            is_watching_parent = Some(self.watched_directory.as_path()) == self.path.parent();
+
+            // The file may have been created along with its directory.
+            has_changed |= is_watching_parent && !was_watching_parent && self.path.exists();
+        }
+
+        has_changed
+    }
+
+    /// Moves the watch to the nearest existing directory of the config file.
+    fn rewatch(&mut self) {
+        let directory = match Self::nearest_directory(&self.path) {
+            Some(directory) if directory != self.watched_directory => directory,
+            _ => return,
+        };
+
+        match Self::add_watch(self.inotify, &self.path, &directory) {
+            Ok(watch) => {
+                // The old watch is already gone if its directory was removed.
+                let _ = self.inotify.rm_watch(self.watch);
+
+                println!("Watching {} for config changes", directory.display());
+
+                self.watch = watch;
+                self.watched_directory = directory;
+            }
+            Err(err) => println!("Failed to watch {}: {}", directory.display(), err),
+        }
+    }
+}
+
+impl AsRawFd for ConfigWatcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inotify.as_raw_fd()
+    }
+}
+
+/// An error that occurred while loading the [`Config`].
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file could not be read.
+    Io(PathBuf, io::Error),
+
+    /// The config file is not valid.
+    Invalid(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Failed to read {}: {}", path.display(), err),
+            Self::Invalid(path, err) => {
+                write!(f, "Invalid config file {}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str(
+            r##"
+                border_width = 2
+                border_color = "#ff0000"
+                layout = "stacking"
+                placement = "least_overlap"
+                close_timeout = 2000
+                workspaces = ["web", "code", "chat"]
+
+                [[bindings]]
+                chord = "super + q"
+                message = "close_window"
+
+                [[bindings]]
+                chord = "super + Return"
+                spawn = "xterm"
+
+                [[rules]]
+                class = "Gimp"
+                manage = false
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.border_width, 2);
+        assert_eq!(config.border_color, HexColor::new(255, 0, 0));
+        assert_eq!(config.gap_width, 16);
+        assert_eq!(config.layout, "stacking");
+        assert_eq!(config.placement, Placement::LeastOverlap);
+        assert_eq!(config.close_timeout, 2000);
+        assert_eq!(config.workspaces, ["web", "code", "chat"]);
+        assert!(matches!(
+            config.bindings[0].action,
+            ipc::Action::Message(ref message) if matches!(**message, ipc::Message::CloseWindow)
+        ));
+        assert!(matches!(config.bindings[1].action, ipc::Action::Spawn(_)));
+        assert_eq!(config.rules[0].manage, Some(false));
+    }
+
+    #[test]
+    fn reject_unknown_keys() {
+        let err = toml::from_str::<Config>("boder_width = 2").unwrap_err();
+
+        assert!(err.to_string().contains("boder_width"));
+    }
+
+    #[test]
+    fn reject_invalid_binding() {
+        assert!(toml::from_str::<Config>(
+            r#"
+                [[bindings]]
+                chord = "hyper + q"
+                spawn = "xterm"
+            "#
+        )
+        .is_err());
+
+        assert!(toml::from_str::<Config>(
+            r#"
+                [[bindings]]
+                chord = "super + q"
+            "#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reject_non_finite_ratio() {
+        assert!(toml::from_str::<Config>("master_ratio = nan").is_err());
+    }
+
+    #[test]
+    fn watch_config_directory_created_later() {
+        let root = env::temp_dir().join(format!("ravenwm-watch-{}", std::process::id()));
+        let path = root.join("ravenwm").join("ravenwm.toml");
+        fs::create_dir_all(&root).unwrap();
+
+        let mut config_watcher = ConfigWatcher::new(&path).unwrap();
+        assert!(!config_watcher.has_changed());
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        assert!(!config_watcher.has_changed());
+
+        fs::write(&path, "gap_width = 4").unwrap();
+        assert!(config_watcher.has_changed());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use xcb::x;
+
+use crate::geometry::Rectangle;
+
+/// The smallest width and height a window can be resized to, including its
+/// border.
+const MIN_SIZE: i32 = 32;
+
+/// What to do with a window, from the direction of a `_NET_WM_MOVERESIZE`
+/// message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveResize {
+    Move,
+    /// Resize by dragging the given edges.
+    Resize {
+        left: bool,
+        top: bool,
+        right: bool,
+        bottom: bool,
+    },
+    /// Stop moving or resizing.
+    Cancel,
+}
+
+impl MoveResize {
+    /// Returns the [`MoveResize`] for the given `_NET_WM_MOVERESIZE`
+    /// direction, if it is done with the pointer.
+    pub fn from_direction(direction: u32) -> Option<Self> {
+        let resize = |left, top, right, bottom| Self::Resize {
+            left,
+            top,
+            right,
+            bottom,
+        };
+
+        match direction {
+            0 => Some(resize(true, true, false, false)),
+            1 => Some(resize(false, true, false, false)),
+            2 => Some(resize(false, true, true, false)),
+            3 => Some(resize(false, false, true, false)),
+            4 => Some(resize(false, false, true, true)),
+            5 => Some(resize(false, false, false, true)),
+            6 => Some(resize(true, false, false, true)),
+            7 => Some(resize(true, false, false, false)),
+            8 => Some(Self::Move),
+            11 => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+}
+
+/// A window being moved or resized with the pointer.
+#[derive(Debug)]
+pub struct Drag {
+    window: x::Window,
+    move_resize: MoveResize,
+    pointer: (i16, i16),
+    rect: Rectangle,
+}
+
+impl Drag {
+    /// Starts moving or resizing the given window with the given area, from
+    /// the given pointer position.
+    pub fn new(
+        window: x::Window,
+        move_resize: MoveResize,
+        pointer: (i16, i16),
+        rect: Rectangle,
+    ) -> Self {
+        Self {
+            window,
+            move_resize,
+            pointer,
+            rect,
+        }
+    }
+
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    /// Returns the area of the window before it was moved or resized.
+    pub fn original_rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    /// Returns the area of the window with the pointer at the given position.
+    pub fn rect_at(&self, pointer: (i16, i16)) -> Rectangle {
+        let dx = pointer.0 as i32 - self.pointer.0 as i32;
+        let dy = pointer.1 as i32 - self.pointer.1 as i32;
+
+        let mut left = self.rect.left() as i32;
+        let mut top = self.rect.top() as i32;
+        let mut right = self.rect.right() as i32;
+        let mut bottom = self.rect.bottom() as i32;
+
+        match self.move_resize {
+            MoveResize::Move => {
+                left += dx;
+                top += dy;
+                right += dx;
+                bottom += dy;
+            }
+            MoveResize::Resize {
+                left: is_left,
+                top: is_top,
+                right: is_right,
+                bottom: is_bottom,
+            } => {
+                if is_left {
+                    left = (left + dx).min(right - MIN_SIZE);
+                }
+
+                if is_top {
+                    top = (top + dy).min(bottom - MIN_SIZE);
+                }
+
+                if is_right {
+                    right = (right + dx).max(left + MIN_SIZE);
+                }
+
+                if is_bottom {
+                    bottom = (bottom + dy).max(top + MIN_SIZE);
+                }
+            }
+            MoveResize::Cancel => {}
+        }
+
+        Rectangle::new(
+            left as i16,
+            top as i16,
+            (right - left) as u16,
+            (bottom - top) as u16,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xcb::XidNew;
+
+    use super::*;
+
+    #[test]
+    fn resizing_keeps_opposite_edges() {
+        let window = unsafe { x::Window::new(1) };
+        let rect = Rectangle::new(100, 100, 200, 200);
+        let top_left = MoveResize::from_direction(0).unwrap();
+
+        let drag = Drag::new(window, top_left, (100, 100), rect);
+
+        assert_eq!(drag.rect_at((90, 120)), Rectangle::new(90, 120, 210, 180));
+        assert_eq!(drag.rect_at((400, 400)), Rectangle::new(268, 268, 32, 32));
+
+        let drag = Drag::new(window, MoveResize::Move, (150, 150), rect);
+
+        assert_eq!(drag.rect_at((160, 140)), Rectangle::new(110, 90, 200, 200));
+    }
+}
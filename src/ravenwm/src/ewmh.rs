@@ -0,0 +1,124 @@
+use xcb::x;
+
+use crate::atoms::Atoms;
+
+/// Returns the `_NET_*` atoms that `ravenwm` supports, for `_NET_SUPPORTED`.
+pub fn supported(atoms: &Atoms) -> Vec<x::Atom> {
+    vec![
+        atoms.net_supported,
+        atoms.net_supporting_wm_check,
+        atoms.net_wm_name,
+        atoms.net_client_list,
+        atoms.net_client_list_stacking,
+        atoms.net_active_window,
+        atoms.net_number_of_desktops,
+        atoms.net_desktop_names,
+        atoms.net_current_desktop,
+        atoms.net_desktop_geometry,
+        atoms.net_desktop_viewport,
+        atoms.net_wm_desktop,
+        atoms.net_close_window,
+        atoms.net_wm_moveresize,
+        atoms.net_wm_state,
+        atoms.net_wm_state_fullscreen,
+    ]
+}
+
+/// The managed windows in the orders `_NET_CLIENT_LIST` and
+/// `_NET_CLIENT_LIST_STACKING` expect.
+#[derive(Debug, Default)]
+pub struct ClientList {
+    mapping_order: Vec<x::Window>,
+    stacking_order: Vec<x::Window>,
+}
+
+impl ClientList {
+    /// Returns the windows in the order they were mapped, oldest first.
+    pub fn mapping_order(&self) -> &[x::Window] {
+        &self.mapping_order
+    }
+
+    /// Returns the windows in stacking order, from bottom to top.
+    pub fn stacking_order(&self) -> &[x::Window] {
+        &self.stacking_order
+    }
+
+    /// Adds a newly managed window on top of the others.
+    pub fn add(&mut self, window: x::Window) {
+        self.remove(window);
+
+        self.mapping_order.push(window);
+        self.stacking_order.push(window);
+    }
+
+    pub fn remove(&mut self, window: x::Window) {
+        self.mapping_order.retain(|other| *other != window);
+        self.stacking_order.retain(|other| *other != window);
+    }
+
+    /// Moves the given window above the others, if it is in the list.
+    ///
+    /// Returns whether the stacking order changed.
+    pub fn raise(&mut self, window: x::Window) -> bool {
+        match self
+            .stacking_order
+            .iter()
+            .position(|other| *other == window)
+        {
+            Some(index) if index + 1 < self.stacking_order.len() => {
+                let window = self.stacking_order.remove(index);
+                self.stacking_order.push(window);
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the given window below the others, if it is in the list.
+    ///
+    /// Returns whether the stacking order changed.
+    pub fn lower(&mut self, window: x::Window) -> bool {
+        match self
+            .stacking_order
+            .iter()
+            .position(|other| *other == window)
+        {
+            Some(index) if index > 0 => {
+                let window = self.stacking_order.remove(index);
+                self.stacking_order.insert(0, window);
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xcb::XidNew;
+
+    use super::*;
+
+    fn window(id: u32) -> x::Window {
+        unsafe { x::Window::new(id) }
+    }
+
+    #[test]
+    fn raising_keeps_mapping_order() {
+        let mut client_list = ClientList::default();
+
+        for id in 1..=3 {
+            client_list.add(window(id));
+        }
+
+        assert!(client_list.raise(window(1)));
+        assert!(!client_list.raise(window(1)));
+        assert!(client_list.lower(window(3)));
+        client_list.remove(window(2));
+
+        assert_eq!(client_list.mapping_order(), &[window(1), window(3)]);
+        assert_eq!(client_list.stacking_order(), &[window(3), window(1)]);
+    }
+}
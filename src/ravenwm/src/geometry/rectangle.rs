@@ -38,12 +38,24 @@ impl Rectangle {
     pub fn inflate(&mut self, width: i16, height: i16) {
         self.x -= width;
         self.y -= height;
-        self.width += 2 * width as u16;
-        self.height += 2 * height as u16;
+        self.width = (self.width as i32 + 2 * width as i32).max(0) as u16;
+        self.height = (self.height as i32 + 2 * height as i32).max(0) as u16;
     }
 
     /// Deflates this [`Rectangle`] by the specified amount.
     pub fn deflate(&mut self, width: i16, height: i16) {
         self.inflate(-width, -height);
     }
+
+    /// Returns the area shared by this [`Rectangle`] and the given one.
+    pub fn overlap(&self, other: &Rectangle) -> u32 {
+        let width = self.right().min(other.right()) as i32 - self.left().max(other.left()) as i32;
+        let height = self.bottom().min(other.bottom()) as i32 - self.top().max(other.top()) as i32;
+
+        if width > 0 && height > 0 {
+            width as u32 * height as u32
+        } else {
+            0
+        }
+    }
 }
@@ -0,0 +1,8 @@
+mod chord;
+mod key_bindings;
+mod keymap;
+mod keysym;
+
+pub use chord::*;
+pub use key_bindings::*;
+pub use keymap::*;
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::str::FromStr;
+
+use xcb::x;
+
+use crate::keyboard::keysym;
+
+/// The modifiers that have no effect on which chord is pressed, so that
+/// bindings keep working while Caps Lock or Num Lock is on.
+pub const IGNORED_MODIFIERS: x::ModMask = x::ModMask::LOCK.union(x::ModMask::N2);
+
+/// The names of the modifiers that can be used in a chord.
+const MODIFIERS: &[(&str, x::ModMask)] = &[
+    ("super", x::ModMask::N4),
+    ("alt", x::ModMask::N1),
+    ("ctrl", x::ModMask::CONTROL),
+    ("control", x::ModMask::CONTROL),
+    ("shift", x::ModMask::SHIFT),
+    ("mod1", x::ModMask::N1),
+    ("mod3", x::ModMask::N3),
+    ("mod4", x::ModMask::N4),
+    ("mod5", x::ModMask::N5),
+];
+
+/// A combination of modifiers and a key, such as `super + alt + w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    modifiers: x::ModMask,
+    keysym: x::Keysym,
+}
+
+impl Chord {
+    /// Creates a new [`Chord`].
+    pub fn new(modifiers: x::ModMask, keysym: x::Keysym) -> Self {
+        Self {
+            modifiers: modifiers - IGNORED_MODIFIERS,
+            keysym,
+        }
+    }
+
+    /// Returns the modifiers that have to be held down.
+    pub fn modifiers(&self) -> x::ModMask {
+        self.modifiers
+    }
+
+    /// Returns the keysym of the key that has to be pressed.
+    pub fn keysym(&self) -> x::Keysym {
+        self.keysym
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<_> = chord.split('+').map(str::trim).collect();
+
+        let key = parts
+            .pop()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| format!("Missing key in chord '{}'", chord))?;
+
+        let mut modifiers = x::ModMask::empty();
+
+        for part in parts {
+            let (_, modifier) = MODIFIERS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(part))
+                .ok_or_else(|| format!("Unknown modifier '{}' in chord '{}'", part, chord))?;
+
+            modifiers |= *modifier;
+        }
+
+        let keysym = keysym::from_name(key)
+            .ok_or_else(|| format!("Unknown key '{}' in chord '{}'", key, chord))?;
+
+        Ok(Self::new(modifiers, keysym))
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            // Skip the aliases so that every modifier is only written once.
+            let is_alias = MODIFIERS
+                .iter()
+                .take_while(|(other_name, _)| other_name != name)
+                .any(|(_, other_modifier)| other_modifier == modifier);
+
+            if !is_alias && self.modifiers.contains(*modifier) {
+                write!(f, "{} + ", name)?;
+            }
+        }
+
+        match keysym::name(self.keysym) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:#x}", self.keysym),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_chord() {
+        let chord: Chord = "super + alt + w".parse().unwrap();
+
+        assert_eq!(
+            chord,
+            Chord::new(x::ModMask::N4 | x::ModMask::N1, 'w' as u32)
+        );
+        assert_eq!(chord.to_string(), "super + alt + w");
+    }
+
+    #[test]
+    fn parse_chord_ignores_case_and_spacing() {
+        let chord: Chord = "Ctrl+Shift+RETURN".parse().unwrap();
+
+        assert_eq!(
+            chord,
+            Chord::new(x::ModMask::CONTROL | x::ModMask::SHIFT, 0xff0d)
+        );
+        assert_eq!(chord.to_string(), "ctrl + shift + Return");
+    }
+
+    #[test]
+    fn parse_invalid_chord() {
+        assert!("".parse::<Chord>().is_err());
+        assert!("super +".parse::<Chord>().is_err());
+        assert!("hyper + w".parse::<Chord>().is_err());
+        assert!("super + nonsense".parse::<Chord>().is_err());
+    }
+}
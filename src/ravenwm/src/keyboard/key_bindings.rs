@@ -0,0 +1,95 @@
+use ravenwm_core::ipc;
+use xcb::x;
+
+use crate::keyboard::{Chord, Keymap, IGNORED_MODIFIERS};
+
+/// The key chords bound to [`ipc::Action`]s.
+#[derive(Default)]
+pub struct KeyBindings {
+    bindings: Vec<(Chord, ipc::Action)>,
+}
+
+impl KeyBindings {
+    /// Binds the given chord to the given action, replacing any previous
+    /// binding of the chord.
+    pub fn bind(&mut self, chord: Chord, action: ipc::Action) {
+        self.unbind(chord);
+        self.bindings.push((chord, action));
+    }
+
+    /// Removes the binding of the given chord.
+    ///
+    /// Returns whether the chord was bound.
+    pub fn unbind(&mut self, chord: Chord) -> bool {
+        let length = self.bindings.len();
+        self.bindings
+            .retain(|(bound_chord, _)| *bound_chord != chord);
+
+        self.bindings.len() != length
+    }
+
+    /// Returns the action bound to the given key, pressed with the given
+    /// modifier state.
+    pub fn find(
+        &self,
+        keymap: &Keymap,
+        keycode: x::Keycode,
+        state: x::KeyButMask,
+    ) -> Option<&ipc::Action> {
+        let modifiers = x::ModMask::from_bits_truncate(state.bits()) - IGNORED_MODIFIERS;
+
+        self.bindings
+            .iter()
+            .find(|(chord, _)| {
+                chord.modifiers() == modifiers && keymap.keycodes(chord.keysym()).contains(&keycode)
+            })
+            .map(|(_, action)| action)
+    }
+
+    /// Grabs the keys of all bound chords on the given window, releasing any
+    /// keys grabbed before.
+    pub fn grab(&self, conn: &xcb::Connection, window: x::Window, keymap: &Keymap) {
+        conn.send_request(&x::UngrabKey {
+            key: x::Grab::Any as x::Keycode,
+            grab_window: window,
+            modifiers: x::ModMask::ANY,
+        });
+
+        let ignored_modifier_combinations = [
+            x::ModMask::empty(),
+            x::ModMask::LOCK,
+            x::ModMask::N2,
+            IGNORED_MODIFIERS,
+        ];
+
+        let mut cookies = Vec::new();
+
+        for (chord, _) in &self.bindings {
+            for key in keymap.keycodes(chord.keysym()) {
+                for ignored_modifiers in ignored_modifier_combinations {
+                    let cookie = conn.send_request_checked(&x::GrabKey {
+                        owner_events: false,
+                        grab_window: window,
+                        modifiers: chord.modifiers() | ignored_modifiers,
+                        key,
+                        pointer_mode: x::GrabMode::Async,
+                        keyboard_mode: x::GrabMode::Async,
+                    });
+
+                    cookies.push((chord, cookie));
+                }
+            }
+        }
+
+        let mut failed_chords = Vec::new();
+
+        for (chord, cookie) in cookies {
+            if let Err(err) = conn.check_request(cookie) {
+                if !failed_chords.contains(&chord) {
+                    println!("Failed to grab '{}': {:?}", chord, err);
+                    failed_chords.push(chord);
+                }
+            }
+        }
+    }
+}
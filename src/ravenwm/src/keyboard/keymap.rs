@@ -0,0 +1,39 @@
+use xcb::x;
+
+/// The keysyms produced by each key of the keyboard.
+pub struct Keymap {
+    min_keycode: x::Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<x::Keysym>,
+}
+
+impl Keymap {
+    /// Fetches the current [`Keymap`] from the X server.
+    pub fn fetch(conn: &xcb::Connection) -> xcb::Result<Self> {
+        let setup = conn.get_setup();
+        let min_keycode = setup.min_keycode();
+        let max_keycode = setup.max_keycode();
+
+        let cookie = conn.send_request(&x::GetKeyboardMapping {
+            first_keycode: min_keycode,
+            count: max_keycode - min_keycode + 1,
+        });
+        let reply = conn.wait_for_reply(cookie)?;
+
+        Ok(Self {
+            min_keycode,
+            keysyms_per_keycode: (reply.keysyms_per_keycode() as usize).max(1),
+            keysyms: reply.keysyms().to_vec(),
+        })
+    }
+
+    /// Returns the keycodes of all keys that produce the given keysym.
+    pub fn keycodes(&self, keysym: x::Keysym) -> Vec<x::Keycode> {
+        self.keysyms
+            .chunks(self.keysyms_per_keycode)
+            .enumerate()
+            .filter(|(_, keysyms)| keysyms.contains(&keysym))
+            .map(|(index, _)| self.min_keycode + index as x::Keycode)
+            .collect()
+    }
+}
@@ -0,0 +1,86 @@
+use xcb::x;
+
+/// The names of the keys that are not letters or digits, as used by X.
+const NAMED_KEYSYMS: &[(&str, x::Keysym)] = &[
+    ("space", 0x0020),
+    ("apostrophe", 0x0027),
+    ("comma", 0x002c),
+    ("minus", 0x002d),
+    ("period", 0x002e),
+    ("slash", 0x002f),
+    ("semicolon", 0x003b),
+    ("equal", 0x003d),
+    ("bracketleft", 0x005b),
+    ("backslash", 0x005c),
+    ("bracketright", 0x005d),
+    ("grave", 0x0060),
+    ("BackSpace", 0xff08),
+    ("Tab", 0xff09),
+    ("Return", 0xff0d),
+    ("Pause", 0xff13),
+    ("Escape", 0xff1b),
+    ("Home", 0xff50),
+    ("Left", 0xff51),
+    ("Up", 0xff52),
+    ("Right", 0xff53),
+    ("Down", 0xff54),
+    ("Prior", 0xff55),
+    ("Next", 0xff56),
+    ("End", 0xff57),
+    ("Page_Up", 0xff55),
+    ("Page_Down", 0xff56),
+    ("Print", 0xff61),
+    ("Insert", 0xff63),
+    ("Menu", 0xff67),
+    ("F1", 0xffbe),
+    ("F2", 0xffbf),
+    ("F3", 0xffc0),
+    ("F4", 0xffc1),
+    ("F5", 0xffc2),
+    ("F6", 0xffc3),
+    ("F7", 0xffc4),
+    ("F8", 0xffc5),
+    ("F9", 0xffc6),
+    ("F10", 0xffc7),
+    ("F11", 0xffc8),
+    ("F12", 0xffc9),
+    ("Delete", 0xffff),
+    ("XF86MonBrightnessUp", 0x1008ff02),
+    ("XF86MonBrightnessDown", 0x1008ff03),
+    ("XF86AudioLowerVolume", 0x1008ff11),
+    ("XF86AudioMute", 0x1008ff12),
+    ("XF86AudioRaiseVolume", 0x1008ff13),
+    ("XF86AudioPlay", 0x1008ff14),
+    ("XF86AudioStop", 0x1008ff15),
+    ("XF86AudioPrev", 0x1008ff16),
+    ("XF86AudioNext", 0x1008ff17),
+];
+
+/// Returns the keysym with the given name, ignoring case.
+///
+/// Letters and digits are named by themselves (e.g., `w` or `1`).
+pub fn from_name(name: &str) -> Option<x::Keysym> {
+    let mut chars = name.chars();
+
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        if char.is_ascii_alphanumeric() {
+            return Some(char.to_ascii_lowercase() as x::Keysym);
+        }
+    }
+
+    NAMED_KEYSYMS
+        .iter()
+        .find(|(keysym_name, _)| keysym_name.eq_ignore_ascii_case(name))
+        .map(|(_, keysym)| *keysym)
+}
+
+/// Returns the name of the given keysym.
+pub fn name(keysym: x::Keysym) -> Option<String> {
+    match char::from_u32(keysym) {
+        Some(char @ ('0'..='9' | 'a'..='z')) => Some(char.to_string()),
+        _ => NAMED_KEYSYMS
+            .iter()
+            .find(|(_, named_keysym)| *named_keysym == keysym)
+            .map(|(name, _)| name.to_string()),
+    }
+}
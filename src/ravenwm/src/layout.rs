@@ -0,0 +1,127 @@
+mod bsp;
+mod columns;
+mod grid;
+mod master_stack;
+mod monocle;
+mod placement;
+mod split;
+
+pub use bsp::*;
+pub use columns::*;
+pub use grid::*;
+pub use master_stack::*;
+pub use monocle::*;
+pub use placement::*;
+pub use split::*;
+
+use ravenwm_core::ipc;
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::Rectangle;
+
+/// The name of the mode where windows are not tiled, but float where they
+/// were placed.
+pub const STACKING: &str = "stacking";
+
+/// The name that switches from [`STACKING`] back to the current tiled layout.
+pub const TILING: &str = "tiling";
+
+/// A way of tiling windows.
+pub trait Layout<T> {
+    /// Returns the name of this layout.
+    fn name(&self) -> &'static str;
+
+    /// Returns the areas of the given windows within the given area, in the
+    /// same order as the windows, with the given gap between them.
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        focused: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle>;
+
+    /// Returns whether windows share their areas, so that the focused one must
+    /// be raised to be seen.
+    fn overlaps(&self) -> bool {
+        false
+    }
+
+    /// Handles a message that is specific to this layout.
+    ///
+    /// Returns `None` if this layout does not support the message.
+    fn handle_message(
+        &mut self,
+        _message: &ipc::Message,
+        _focused: Option<T>,
+    ) -> Option<Result<(), String>> {
+        None
+    }
+
+    /// Saves the settings of this layout into the given state, to hand them
+    /// over on restart.
+    fn save(&self, _state: &mut LayoutState<T>) {}
+
+    /// Restores the settings saved by [`Layout::save`].
+    fn restore(&mut self, _state: &LayoutState<T>) {}
+}
+
+/// The settings of the layouts of a workspace that survive a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutState<T> {
+    #[serde(default)]
+    pub master_count: Option<usize>,
+    #[serde(default)]
+    pub master_ratio: Option<f32>,
+
+    /// The split tree of the [`BspTree`] layout.
+    #[serde(default)]
+    pub bsp: Option<Node<T>>,
+}
+
+impl<T> Default for LayoutState<T> {
+    fn default() -> Self {
+        Self {
+            master_count: None,
+            master_ratio: None,
+            bsp: None,
+        }
+    }
+}
+
+impl<T> LayoutState<T> {
+    /// Maps the windows in this state with the given function, dropping the
+    /// ones it returns `None` for.
+    pub fn filter_map<U>(self, mut f: impl FnMut(T) -> Option<U>) -> LayoutState<U> {
+        LayoutState {
+            master_count: self.master_count,
+            master_ratio: self.master_ratio,
+            bsp: self.bsp.and_then(|root| root.filter_map(&mut f)),
+        }
+    }
+}
+
+/// Returns all of the built-in layouts, starting with the default one.
+pub fn all<T: Copy + PartialEq + 'static>(
+    master_count: usize,
+    master_ratio: f32,
+) -> Vec<Box<dyn Layout<T>>> {
+    vec![
+        Box::new(MasterStack::new(master_count, master_ratio)),
+        Box::new(BspTree::new()),
+        Box::new(Monocle),
+        Box::new(Grid),
+        Box::new(Columns),
+        Box::new(Rows),
+    ]
+}
+
+/// Returns whether the given name refers to a layout, [`STACKING`] or
+/// [`TILING`].
+pub fn is_known(name: &str) -> bool {
+    name == STACKING
+        || name == TILING
+        || all::<u32>(1, 0.5)
+            .iter()
+            .any(|layout| layout.name() == name)
+}
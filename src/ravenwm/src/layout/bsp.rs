@@ -0,0 +1,587 @@
+use ravenwm_core::ipc;
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::Rectangle;
+use crate::layout::{Layout, LayoutState};
+
+/// The smallest share of a split that either side can take up.
+const MIN_SPLIT_RATIO: f32 = 0.05;
+
+/// The largest share of a split that either side can take up.
+const MAX_SPLIT_RATIO: f32 = 0.95;
+
+/// How the two children of a split are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    /// The children are side by side, the first one on the left.
+    Horizontal,
+
+    /// The children are on top of each other, the first one at the top.
+    Vertical,
+}
+
+/// A node of a [`BspTree`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Node<T> {
+    /// A window.
+    Leaf(T),
+
+    /// An area split between two nodes.
+    Split {
+        orientation: Orientation,
+        /// The share of the area taken up by the first node.
+        ratio: f32,
+        first: Box<Node<T>>,
+        second: Box<Node<T>>,
+    },
+}
+
+impl<T> Node<T> {
+    /// Maps the windows in this subtree with the given function, dropping the
+    /// ones it returns `None` for like [`Node::remove`] does.
+    pub fn filter_map<U>(self, f: &mut impl FnMut(T) -> Option<U>) -> Option<Node<U>> {
+        match self {
+            Self::Leaf(leaf) => f(leaf).map(Node::Leaf),
+            Self::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => match (first.filter_map(f), second.filter_map(f)) {
+                (Some(first), Some(second)) => Some(Node::Split {
+                    orientation,
+                    ratio,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(remaining), None) | (None, Some(remaining)) => Some(remaining),
+                (None, None) => None,
+            },
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> Node<T> {
+    /// Returns whether the given window is in this subtree.
+    fn contains(&self, window: T) -> bool {
+        match self {
+            Self::Leaf(leaf) => *leaf == window,
+            Self::Split { first, second, .. } => first.contains(window) || second.contains(window),
+        }
+    }
+
+    /// Returns the number of windows in this subtree.
+    fn leaf_count(&self) -> usize {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Split { first, second, .. } => first.leaf_count() + second.leaf_count(),
+        }
+    }
+
+    /// Appends the windows in this subtree to the given list, in order.
+    fn collect_leaves(&self, leaves: &mut Vec<T>) {
+        match self {
+            Self::Leaf(leaf) => leaves.push(*leaf),
+            Self::Split { first, second, .. } => {
+                first.collect_leaves(leaves);
+                second.collect_leaves(leaves);
+            }
+        }
+    }
+
+    /// Returns the area of the given window, if it is in this subtree.
+    fn find_area(&self, area: Rectangle, gap: u16, window: T) -> Option<Rectangle> {
+        match self {
+            Self::Leaf(leaf) => (*leaf == window).then_some(area),
+            Self::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_area, second_area) = split_area(area, *orientation, *ratio, gap);
+
+                first
+                    .find_area(first_area, gap, window)
+                    .or_else(|| second.find_area(second_area, gap, window))
+            }
+        }
+    }
+
+    /// Replaces the leaf of the given window with a split between it and the
+    /// new window.
+    ///
+    /// Returns whether the window was found.
+    fn split_leaf(&mut self, target: T, window: T, orientation: Orientation) -> bool {
+        match self {
+            Self::Leaf(leaf) if *leaf == target => {
+                *self = Self::Split {
+                    orientation,
+                    ratio: 0.5,
+                    first: Box::new(Self::Leaf(target)),
+                    second: Box::new(Self::Leaf(window)),
+                };
+
+                true
+            }
+            Self::Leaf(_) => false,
+            Self::Split { first, second, .. } => {
+                first.split_leaf(target, window, orientation)
+                    || second.split_leaf(target, window, orientation)
+            }
+        }
+    }
+
+    /// Removes the given window from this subtree.
+    ///
+    /// Returns the remaining subtree, which is `None` if it was just the window.
+    fn remove(self, window: T) -> Option<Self> {
+        match self {
+            Self::Leaf(leaf) if leaf == window => None,
+            Self::Leaf(_) => Some(self),
+            Self::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => match (first.remove(window), second.remove(window)) {
+                (Some(first), Some(second)) => Some(Self::Split {
+                    orientation,
+                    ratio,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(remaining), None) | (None, Some(remaining)) => Some(remaining),
+                (None, None) => None,
+            },
+        }
+    }
+
+    /// Returns the innermost split that directly contains the given window.
+    fn parent_of(&mut self, window: T) -> Option<&mut Self> {
+        let is_parent = match self {
+            Self::Leaf(_) => return None,
+            Self::Split { first, second, .. } => {
+                matches!(**first, Self::Leaf(leaf) if leaf == window)
+                    || matches!(**second, Self::Leaf(leaf) if leaf == window)
+            }
+        };
+
+        if is_parent {
+            return Some(self);
+        }
+
+        match self {
+            Self::Split { first, second, .. } => {
+                if first.contains(window) {
+                    first.parent_of(window)
+                } else {
+                    second.parent_of(window)
+                }
+            }
+            Self::Leaf(_) => None,
+        }
+    }
+
+    /// Rotates this subtree clockwise by 90 degrees.
+    fn rotate_clockwise(&mut self) {
+        if let Self::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            match orientation {
+                // The left node ends up at the top.
+                Orientation::Horizontal => *orientation = Orientation::Vertical,
+                // The top node ends up on the right.
+                Orientation::Vertical => {
+                    *orientation = Orientation::Horizontal;
+                    *ratio = 1.0 - *ratio;
+                    std::mem::swap(first, second);
+                }
+            }
+
+            first.rotate_clockwise();
+            second.rotate_clockwise();
+        }
+    }
+
+    /// Mirrors this subtree, swapping the children of all splits with the
+    /// given orientation.
+    fn flip(&mut self, flipped_orientation: Orientation) {
+        if let Self::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            if *orientation == flipped_orientation {
+                *ratio = 1.0 - *ratio;
+                std::mem::swap(first, second);
+            }
+
+            first.flip(flipped_orientation);
+            second.flip(flipped_orientation);
+        }
+    }
+
+    /// Sets the ratios of all splits so that every window gets the same area.
+    fn balance(&mut self) {
+        if let Self::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        {
+            let first_count = first.leaf_count();
+            *ratio = first_count as f32 / (first_count + second.leaf_count()) as f32;
+
+            first.balance();
+            second.balance();
+        }
+    }
+
+    /// Appends the areas of all windows in this subtree to the given list.
+    fn arrange(&self, area: Rectangle, gap: u16, areas: &mut Vec<(T, Rectangle)>) {
+        match self {
+            Self::Leaf(leaf) => areas.push((*leaf, area)),
+            Self::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_area, second_area) = split_area(area, *orientation, *ratio, gap);
+
+                first.arrange(first_area, gap, areas);
+                second.arrange(second_area, gap, areas);
+            }
+        }
+    }
+}
+
+/// A layout that recursively splits the screen in two, bspwm-style.
+///
+/// New windows split the area of the focused window.
+#[derive(Debug, Clone)]
+pub struct BspTree<T> {
+    root: Option<Node<T>>,
+}
+
+impl<T: Copy + PartialEq> BspTree<T> {
+    /// Creates a new, empty [`BspTree`].
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns the windows in the tree, in order.
+    pub fn windows(&self) -> Vec<T> {
+        let mut windows = Vec::new();
+
+        if let Some(root) = &self.root {
+            root.collect_leaves(&mut windows);
+        }
+
+        windows
+    }
+
+    /// Inserts the given window by splitting the area of the target window,
+    /// or of the last window if the target is not in the tree.
+    ///
+    /// The area is split along its longest side.
+    pub fn insert(&mut self, window: T, target: Option<T>, area: Rectangle, gap: u16) {
+        let windows = self.windows();
+
+        let Some(root) = &mut self.root else {
+            self.root = Some(Node::Leaf(window));
+            return;
+        };
+
+        let Some(target) = target
+            .filter(|target| windows.contains(target))
+            .or_else(|| windows.last().copied())
+        else {
+            return;
+        };
+
+        let target_area = root.find_area(area, gap, target).unwrap_or(area);
+        let orientation = if target_area.width >= target_area.height {
+            Orientation::Horizontal
+        } else {
+            Orientation::Vertical
+        };
+
+        root.split_leaf(target, window, orientation);
+    }
+
+    /// Removes the given window, giving its area to its sibling.
+    pub fn remove(&mut self, window: T) {
+        self.root = self.root.take().and_then(|root| root.remove(window));
+    }
+
+    /// Makes the tree contain exactly the given windows.
+    ///
+    /// Windows that are no longer given are removed, and new ones are
+    /// inserted next to the target window.
+    pub fn sync(&mut self, windows: &[T], target: Option<T>, area: Rectangle, gap: u16) {
+        for window in self.windows() {
+            if !windows.contains(&window) {
+                self.remove(window);
+            }
+        }
+
+        let existing_windows = self.windows();
+
+        for window in windows {
+            if !existing_windows.contains(window) {
+                self.insert(*window, target, area, gap);
+            }
+        }
+    }
+
+    /// Applies the given command to the tree.
+    ///
+    /// Rotating, flipping and changing the ratio affect the split containing
+    /// the given window, or the whole tree if there is none. Balancing always
+    /// affects the whole tree.
+    pub fn apply(&mut self, command: ipc::BspCommand, window: Option<T>) -> Result<(), String> {
+        // Check the arguments first, even if there is nothing to apply them to.
+        let quarter_turns = match command {
+            ipc::BspCommand::Rotate { degrees } => match degrees {
+                90 => 1,
+                180 => 2,
+                270 => 3,
+                _ => return Err(format!("Cannot rotate by {} degrees", degrees)),
+            },
+            ipc::BspCommand::SetRatio(adjustment) if !adjustment.is_finite() => {
+                return Err("The split ratio must be a finite number".to_string());
+            }
+            _ => 0,
+        };
+
+        let Some(root) = &mut self.root else {
+            return Ok(());
+        };
+
+        let node = match (command, window) {
+            (ipc::BspCommand::Balance, _) => root,
+            (_, Some(window)) if root.contains(window) => match root.parent_of(window) {
+                Some(parent) => parent,
+                None => return Ok(()),
+            },
+            _ => root,
+        };
+
+        match command {
+            ipc::BspCommand::Rotate { .. } => {
+                for _ in 0..quarter_turns {
+                    node.rotate_clockwise();
+                }
+            }
+            ipc::BspCommand::Flip(ipc::Flip::Horizontal) => node.flip(Orientation::Horizontal),
+            ipc::BspCommand::Flip(ipc::Flip::Vertical) => node.flip(Orientation::Vertical),
+            ipc::BspCommand::SetRatio(adjustment) => {
+                if let Node::Split { ratio, .. } = node {
+                    *ratio = adjustment
+                        .apply(*ratio)
+                        .clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+                }
+            }
+            ipc::BspCommand::Balance => node.balance(),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the areas of all windows within the given area, with the given
+    /// gap between them.
+    pub fn areas(&self, area: Rectangle, gap: u16) -> Vec<(T, Rectangle)> {
+        let mut areas = Vec::new();
+
+        if let Some(root) = &self.root {
+            root.arrange(area, gap, &mut areas);
+        }
+
+        areas
+    }
+}
+
+impl<T: Copy + PartialEq> Layout<T> for BspTree<T> {
+    fn name(&self) -> &'static str {
+        "bsp"
+    }
+
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        focused: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle> {
+        self.sync(windows, focused, area, gap);
+
+        let areas = self.areas(area, gap);
+
+        windows
+            .iter()
+            .map(|window| {
+                areas
+                    .iter()
+                    .find(|(leaf, _)| leaf == window)
+                    .map(|(_, area)| *area)
+                    .unwrap_or(area)
+            })
+            .collect()
+    }
+
+    fn handle_message(
+        &mut self,
+        message: &ipc::Message,
+        focused: Option<T>,
+    ) -> Option<Result<(), String>> {
+        match *message {
+            ipc::Message::Bsp(command) => Some(self.apply(command, focused)),
+            _ => None,
+        }
+    }
+
+    fn save(&self, state: &mut LayoutState<T>) {
+        state.bsp = self.root.clone();
+    }
+
+    fn restore(&mut self, state: &LayoutState<T>) {
+        if let Some(root) = &state.bsp {
+            self.root = Some(root.clone());
+        }
+    }
+}
+
+/// Splits the given area in two at the given ratio, with the given gap
+/// between the halves.
+fn split_area(
+    area: Rectangle,
+    orientation: Orientation,
+    ratio: f32,
+    gap: u16,
+) -> (Rectangle, Rectangle) {
+    match orientation {
+        Orientation::Horizontal => {
+            let first_width = ((area.width.saturating_sub(gap)) as f32 * ratio) as u16;
+            let second_width = area.width.saturating_sub(first_width + gap);
+
+            (
+                Rectangle::new(area.x, area.y, first_width, area.height),
+                Rectangle::new(
+                    area.x + (first_width + gap) as i16,
+                    area.y,
+                    second_width,
+                    area.height,
+                ),
+            )
+        }
+        Orientation::Vertical => {
+            let first_height = ((area.height.saturating_sub(gap)) as f32 * ratio) as u16;
+            let second_height = area.height.saturating_sub(first_height + gap);
+
+            (
+                Rectangle::new(area.x, area.y, area.width, first_height),
+                Rectangle::new(
+                    area.x,
+                    area.y + (first_height + gap) as i16,
+                    area.width,
+                    second_height,
+                ),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rectangle = Rectangle {
+        x: 0,
+        y: 0,
+        width: 1000,
+        height: 600,
+    };
+
+    #[test]
+    fn new_windows_split_the_target() {
+        let mut tree = BspTree::new();
+        tree.insert(1, None, AREA, 0);
+        tree.insert(2, Some(1), AREA, 0);
+        tree.insert(3, Some(1), AREA, 0);
+
+        assert_eq!(
+            tree.areas(AREA, 0),
+            vec![
+                (1, Rectangle::new(0, 0, 500, 300)),
+                (3, Rectangle::new(0, 300, 500, 300)),
+                (2, Rectangle::new(500, 0, 500, 600)),
+            ]
+        );
+    }
+
+    #[test]
+    fn removing_a_window_gives_its_area_to_its_sibling() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2, 3], None, AREA, 0);
+        tree.sync(&[1, 3], None, AREA, 0);
+
+        assert_eq!(
+            tree.areas(AREA, 0),
+            vec![
+                (1, Rectangle::new(0, 0, 500, 600)),
+                (3, Rectangle::new(500, 0, 500, 600)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rotate_and_flip() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2], None, AREA, 0);
+
+        tree.apply(ipc::BspCommand::Rotate { degrees: 90 }, None)
+            .unwrap();
+        assert_eq!(
+            tree.areas(AREA, 0),
+            vec![
+                (1, Rectangle::new(0, 0, 1000, 300)),
+                (2, Rectangle::new(0, 300, 1000, 300)),
+            ]
+        );
+
+        tree.apply(ipc::BspCommand::Flip(ipc::Flip::Vertical), Some(1))
+            .unwrap();
+        assert_eq!(tree.windows(), vec![2, 1]);
+
+        assert!(tree
+            .apply(ipc::BspCommand::Rotate { degrees: 45 }, None)
+            .is_err());
+
+        // Invalid arguments are rejected even without a tree.
+        assert!(BspTree::<u32>::new()
+            .apply(ipc::BspCommand::Rotate { degrees: 45 }, None)
+            .is_err());
+    }
+
+    #[test]
+    fn balance() {
+        let mut tree = BspTree::new();
+        tree.sync(&[1, 2, 3], None, AREA, 0);
+        tree.apply(ipc::BspCommand::Balance, None).unwrap();
+
+        let areas = tree.areas(AREA, 0);
+
+        assert_eq!(areas[0], (1, Rectangle::new(0, 0, 333, 600)));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::geometry::Rectangle;
+use crate::layout::{split_columns, split_rows, Layout};
+
+/// A layout where the windows are side by side in columns of equal width.
+#[derive(Debug, Default)]
+pub struct Columns;
+
+impl<T> Layout<T> for Columns {
+    fn name(&self) -> &'static str {
+        "columns"
+    }
+
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        _: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle> {
+        split_columns(area, windows.len(), gap)
+    }
+}
+
+/// A layout where the windows are on top of each other in rows of equal
+/// height.
+#[derive(Debug, Default)]
+pub struct Rows;
+
+impl<T> Layout<T> for Rows {
+    fn name(&self) -> &'static str {
+        "rows"
+    }
+
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        _: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle> {
+        split_rows(area, windows.len(), gap)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::geometry::Rectangle;
+use crate::layout::{split_columns, split_rows, Layout};
+
+/// A layout where the windows are in a grid with about as many columns as
+/// rows.
+///
+/// The windows in an incomplete last row share its whole width.
+#[derive(Debug, Default)]
+pub struct Grid;
+
+impl<T> Layout<T> for Grid {
+    fn name(&self) -> &'static str {
+        "grid"
+    }
+
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        _: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle> {
+        let count = windows.len();
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let column_count = (count as f64).sqrt().ceil() as usize;
+        let row_count = count.div_ceil(column_count);
+
+        split_rows(area, row_count, gap)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, row_area)| {
+                let columns_in_row = column_count.min(count - row * column_count);
+
+                split_columns(row_area, columns_in_row, gap)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incomplete_last_row_is_stretched() {
+        let area = Rectangle::new(0, 0, 900, 600);
+
+        let rects = Layout::<u32>::arrange(&mut Grid, area, &[1, 2, 3], None, 0);
+
+        assert_eq!(
+            rects,
+            vec![
+                Rectangle::new(0, 0, 450, 300),
+                Rectangle::new(450, 0, 450, 300),
+                Rectangle::new(0, 300, 900, 300),
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use ravenwm_core::ipc;
+
+use crate::geometry::Rectangle;
+use crate::layout::{split_rows, Layout, LayoutState};
+
+/// The smallest share of the screen the master area can take up.
+const MIN_MASTER_RATIO: f32 = 0.05;
+
+/// The largest share of the screen the master area can take up.
+const MAX_MASTER_RATIO: f32 = 0.95;
+
+/// A layout with a column of master windows on the left and a column of the
+/// remaining windows stacked on the right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterStack {
+    master_count: usize,
+    master_ratio: f32,
+}
+
+impl MasterStack {
+    /// Creates a new [`MasterStack`] layout.
+    pub fn new(master_count: usize, master_ratio: f32) -> Self {
+        Self {
+            master_count,
+            master_ratio: master_ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO),
+        }
+    }
+
+    /// Sets the number of windows in the master area.
+    pub fn set_master_count(&mut self, master_count: usize) {
+        self.master_count = master_count;
+    }
+
+    /// Sets the share of the screen taken up by the master area.
+    pub fn set_master_ratio(&mut self, master_ratio: f32) {
+        self.master_ratio = master_ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+    }
+
+    /// Returns the areas of the given number of windows within the given
+    /// area, with the given gap between them.
+    fn arrange_count(&self, area: Rectangle, count: usize, gap: u16) -> Vec<Rectangle> {
+        let master_count = self.master_count.min(count);
+        let stack_count = count - master_count;
+
+        if master_count == 0 || stack_count == 0 {
+            return split_rows(area, count, gap);
+        }
+
+        let master_width = ((area.width as f32 - gap as f32) * self.master_ratio) as u16;
+        let stack_width = area.width.saturating_sub(master_width + gap);
+
+        let master_area = Rectangle::new(area.x, area.y, master_width, area.height);
+        let stack_area = Rectangle::new(
+            area.x + (master_width + gap) as i16,
+            area.y,
+            stack_width,
+            area.height,
+        );
+
+        let mut rects = split_rows(master_area, master_count, gap);
+        rects.extend(split_rows(stack_area, stack_count, gap));
+
+        rects
+    }
+}
+
+impl<T> Layout<T> for MasterStack {
+    fn name(&self) -> &'static str {
+        "master_stack"
+    }
+
+    fn arrange(
+        &mut self,
+        area: Rectangle,
+        windows: &[T],
+        _: Option<T>,
+        gap: u16,
+    ) -> Vec<Rectangle> {
+        self.arrange_count(area, windows.len(), gap)
+    }
+
+    fn handle_message(
+        &mut self,
+        message: &ipc::Message,
+        _: Option<T>,
+    ) -> Option<Result<(), String>> {
+        match *message {
+            ipc::Message::SetMasterRatio { ratio } => {
+                if !ratio.is_finite() {
+                    return Some(Err("The master ratio must be a finite number".to_string()));
+                }
+
+                self.set_master_ratio(ratio.apply(self.master_ratio));
+            }
+            ipc::Message::SetMasterCount { count } => {
+                let master_count = count.apply_saturating(self.master_count as i32);
+                self.set_master_count(master_count.max(0) as usize);
+            }
+            _ => return None,
+        }
+
+        Some(Ok(()))
+    }
+
+    fn save(&self, state: &mut LayoutState<T>) {
+        state.master_count = Some(self.master_count);
+        state.master_ratio = Some(self.master_ratio);
+    }
+
+    fn restore(&mut self, state: &LayoutState<T>) {
+        if let Some(master_count) = state.master_count {
+            self.set_master_count(master_count);
+        }
+
+        if let Some(master_ratio) = state.master_ratio {
+            self.set_master_ratio(master_ratio);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_window_fills_area() {
+        let area = Rectangle::new(10, 10, 1000, 700);
+
+        assert_eq!(
+            MasterStack::new(1, 0.5).arrange_count(area, 1, 10),
+            vec![area]
+        );
+    }
+
+    #[test]
+    fn master_and_stack() {
+        let area = Rectangle::new(0, 0, 1010, 710);
+
+        let rects = MasterStack::new(1, 0.6).arrange_count(area, 3, 10);
+
+        assert_eq!(
+            rects,
+            vec![
+                Rectangle::new(0, 0, 600, 710),
+                Rectangle::new(610, 0, 400, 350),
+                Rectangle::new(610, 360, 400, 350),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_master_windows() {
+        let area = Rectangle::new(0, 0, 1000, 710);
+
+        let rects = MasterStack::new(0, 0.5).arrange_count(area, 2, 10);
+
+        assert_eq!(
+            rects,
+            vec![
+                Rectangle::new(0, 0, 1000, 350),
+                Rectangle::new(0, 360, 1000, 350),
+            ]
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use crate::geometry::Rectangle;
+use crate::layout::Layout;
+
+/// A layout where every window takes up the whole screen.
+#[derive(Debug, Default)]
+pub struct Monocle;
+
+impl<T> Layout<T> for Monocle {
+    fn name(&self) -> &'static str {
+        "monocle"
+    }
+
+    fn arrange(&mut self, area: Rectangle, windows: &[T], _: Option<T>, _: u16) -> Vec<Rectangle> {
+        vec![area; windows.len()]
+    }
+
+    fn overlaps(&self) -> bool {
+        true
+    }
+}
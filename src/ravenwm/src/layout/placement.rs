@@ -0,0 +1,159 @@
+use serde::Deserialize;
+
+use crate::geometry::Rectangle;
+
+/// The offset between windows placed by [`Placement::Cascade`].
+const CASCADE_STEP: i32 = 32;
+
+/// A policy for placing new windows in the stacking layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    /// Places windows in the center of the screen.
+    #[default]
+    Centered,
+
+    /// Places windows centered under the pointer.
+    UnderPointer,
+
+    /// Places each window a bit below and to the right of the previous one.
+    Cascade,
+
+    /// Places windows where they cover as little of the other windows as
+    /// possible.
+    LeastOverlap,
+}
+
+impl Placement {
+    /// Returns the area of a new window of the given size within the given
+    /// area, next to the given windows.
+    pub fn place(
+        self,
+        width: u16,
+        height: u16,
+        area: Rectangle,
+        pointer: (i16, i16),
+        others: &[Rectangle],
+    ) -> Rectangle {
+        let width = width.min(area.width);
+        let height = height.min(area.height);
+
+        let (x, y) = match self {
+            Self::Centered => (
+                area.x as i32 + (area.width - width) as i32 / 2,
+                area.y as i32 + (area.height - height) as i32 / 2,
+            ),
+            Self::UnderPointer => (
+                pointer.0 as i32 - width as i32 / 2,
+                pointer.1 as i32 - height as i32 / 2,
+            ),
+            Self::Cascade => cascade(width, height, area, others),
+            Self::LeastOverlap => least_overlap(width, height, area, others),
+        };
+
+        let x = x.clamp(area.left() as i32, (area.right() - width as i16) as i32);
+        let y = y.clamp(area.top() as i32, (area.bottom() - height as i16) as i32);
+
+        Rectangle::new(x as i16, y as i16, width, height)
+    }
+}
+
+/// Returns the first step of the cascade that no other window starts at,
+/// starting over at the top left of the area when the cascade runs out of it.
+fn cascade(width: u16, height: u16, area: Rectangle, others: &[Rectangle]) -> (i32, i32) {
+    let start = (area.x as i32, area.y as i32);
+
+    (0..)
+        .map(|step| (start.0 + step * CASCADE_STEP, start.1 + step * CASCADE_STEP))
+        .take_while(|(x, y)| {
+            x + width as i32 <= area.right() as i32 && y + height as i32 <= area.bottom() as i32
+        })
+        .find(|(x, y)| {
+            !others
+                .iter()
+                .any(|other| other.x as i32 == *x && other.y as i32 == *y)
+        })
+        .unwrap_or(start)
+}
+
+/// Returns the position that overlaps the other windows the least, trying
+/// the edges of the area and of the other windows, from the top left.
+fn least_overlap(width: u16, height: u16, area: Rectangle, others: &[Rectangle]) -> (i32, i32) {
+    let candidates = |start: i16, end: i16, length: u16, edges: &dyn Fn(&Rectangle) -> i16| {
+        let mut candidates: Vec<i16> = [start, end - length as i16]
+            .into_iter()
+            .chain(others.iter().map(edges))
+            .filter(|position| *position >= start && *position + length as i16 <= end)
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    };
+
+    let xs = candidates(area.left(), area.right(), width, &|other| other.right());
+    let ys = candidates(area.top(), area.bottom(), height, &|other| other.bottom());
+
+    let mut best = (area.x as i32, area.y as i32);
+    let mut best_overlap = u32::MAX;
+
+    for &y in &ys {
+        for &x in &xs {
+            let rect = Rectangle::new(x, y, width, height);
+            let overlap = others.iter().map(|other| rect.overlap(other)).sum();
+
+            if overlap < best_overlap {
+                best = (x as i32, y as i32);
+                best_overlap = overlap;
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rectangle = Rectangle {
+        x: 0,
+        y: 0,
+        width: 1000,
+        height: 800,
+    };
+
+    #[test]
+    fn centered_and_under_pointer_stay_on_screen() {
+        assert_eq!(
+            Placement::Centered.place(400, 200, AREA, (0, 0), &[]),
+            Rectangle::new(300, 300, 400, 200)
+        );
+        assert_eq!(
+            Placement::UnderPointer.place(400, 200, AREA, (950, 100), &[]),
+            Rectangle::new(600, 0, 400, 200)
+        );
+    }
+
+    #[test]
+    fn cascade_skips_taken_steps() {
+        let others = [
+            Rectangle::new(0, 0, 400, 200),
+            Rectangle::new(32, 32, 400, 200),
+        ];
+
+        assert_eq!(
+            Placement::Cascade.place(400, 200, AREA, (0, 0), &others),
+            Rectangle::new(64, 64, 400, 200)
+        );
+    }
+
+    #[test]
+    fn least_overlap_finds_free_space() {
+        let others = [Rectangle::new(0, 0, 600, 800)];
+
+        assert_eq!(
+            Placement::LeastOverlap.place(400, 200, AREA, (0, 0), &others),
+            Rectangle::new(600, 0, 400, 200)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use crate::geometry::Rectangle;
+
+/// Splits the given area into the given number of rows of equal height, with
+/// the given gap between them.
+///
+/// The last row takes up any pixels left over.
+pub fn split_rows(area: Rectangle, count: usize, gap: u16) -> Vec<Rectangle> {
+    split_lengths(area.height, count, gap)
+        .into_iter()
+        .map(|(offset, height)| Rectangle::new(area.x, area.y + offset, area.width, height))
+        .collect()
+}
+
+/// Splits the given area into the given number of columns of equal width,
+/// with the given gap between them.
+///
+/// The last column takes up any pixels left over.
+pub fn split_columns(area: Rectangle, count: usize, gap: u16) -> Vec<Rectangle> {
+    split_lengths(area.width, count, gap)
+        .into_iter()
+        .map(|(offset, width)| Rectangle::new(area.x + offset, area.y, width, area.height))
+        .collect()
+}
+
+/// Splits the given length into the given number of equal parts, with the
+/// given gap between them, and returns the offset and length of each part.
+fn split_lengths(length: u16, count: usize, gap: u16) -> Vec<(i16, u16)> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let gaps = gap as u32 * (count as u32 - 1);
+    let part_length = (length as u32).saturating_sub(gaps) / count as u32;
+
+    (0..count)
+        .map(|index| {
+            let offset = index as u32 * (part_length + gap as u32);
+            let part_length = if index == count - 1 {
+                (length as u32).saturating_sub(offset)
+            } else {
+                part_length
+            };
+
+            (offset as i16, part_length as u16)
+        })
+        .collect()
+}
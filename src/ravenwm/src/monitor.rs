@@ -0,0 +1,111 @@
+use xcb::{randr, x};
+
+use crate::geometry::Rectangle;
+
+/// A monitor, showing one of the workspaces.
+#[derive(Debug)]
+pub struct Monitor {
+    name: String,
+    rect: Rectangle,
+    workspace: usize,
+}
+
+impl Monitor {
+    /// Creates a new [`Monitor`] with the given name and area, showing the
+    /// workspace with the given index.
+    pub fn new(name: String, rect: Rectangle, workspace: usize) -> Self {
+        Self {
+            name,
+            rect,
+            workspace,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    /// Returns the index of the workspace shown on this monitor.
+    pub fn workspace(&self) -> usize {
+        self.workspace
+    }
+
+    /// Shows the workspace with the given index on this monitor.
+    pub fn set_workspace(&mut self, workspace: usize) {
+        self.workspace = workspace;
+    }
+}
+
+/// Returns whether RandR 1.5, which knows about monitors, is available.
+pub fn has_randr(conn: &xcb::Connection) -> bool {
+    if !conn
+        .active_extensions()
+        .any(|extension| extension == xcb::Extension::RandR)
+    {
+        return false;
+    }
+
+    let cookie = conn.send_request(&randr::QueryVersion {
+        major_version: 1,
+        minor_version: 5,
+    });
+
+    match conn.wait_for_reply(cookie) {
+        Ok(version) => (version.major_version(), version.minor_version()) >= (1, 5),
+        Err(_) => false,
+    }
+}
+
+/// Returns the name and area of every monitor of the screen with the given
+/// root window, from left to right.
+///
+/// Falls back to a single monitor covering the given screen area if there is
+/// no RandR or it reports no monitors.
+pub fn fetch_monitors(
+    conn: &xcb::Connection,
+    root: x::Window,
+    screen_rect: Rectangle,
+) -> xcb::Result<Vec<(String, Rectangle)>> {
+    if !has_randr(conn) {
+        return Ok(vec![("screen".to_string(), screen_rect)]);
+    }
+
+    let reply = conn.wait_for_reply(conn.send_request(&randr::GetMonitors {
+        window: root,
+        get_active: true,
+    }))?;
+
+    let name_cookies: Vec<_> = reply
+        .monitors()
+        .map(|monitor| {
+            conn.send_request(&x::GetAtomName {
+                atom: monitor.name(),
+            })
+        })
+        .collect();
+
+    let mut monitors = Vec::new();
+
+    for (monitor, name_cookie) in reply.monitors().zip(name_cookies) {
+        let name = conn
+            .wait_for_reply(name_cookie)?
+            .name()
+            .to_utf8()
+            .into_owned();
+        let rect = Rectangle::new(monitor.x(), monitor.y(), monitor.width(), monitor.height());
+
+        monitors.push((name, rect));
+    }
+
+    if monitors.is_empty() {
+        return Ok(vec![("screen".to_string(), screen_rect)]);
+    }
+
+    monitors.sort_by_key(|(_, rect)| (rect.x, rect.y));
+
+    Ok(monitors)
+}
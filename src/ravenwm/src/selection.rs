@@ -0,0 +1,243 @@
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use xcb::{x, Xid};
+
+/// How long to wait for the window manager being replaced to give up its
+/// windows.
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Ownership of the ICCCM `WM_S<n>` selection, which marks `ravenwm` as the
+/// window manager of a screen.
+#[derive(Debug)]
+pub struct WmSelection {
+    window: x::Window,
+    atom: x::Atom,
+}
+
+impl WmSelection {
+    /// Becomes the window manager of the given screen, replacing the running
+    /// one if `replace` is set.
+    ///
+    /// Owns the selection with a new window and selects
+    /// `SUBSTRUCTURE_REDIRECT` on the root window, which fails if any other
+    /// window manager is still running. Clients are only told about the new
+    /// window manager once both succeeded.
+    pub fn acquire(
+        conn: &xcb::Connection,
+        screen_number: i32,
+        replace: bool,
+    ) -> Result<Self, StartError> {
+        let setup = conn.get_setup();
+        let root = setup.roots().nth(screen_number as usize).unwrap().root();
+
+        let atom_cookie = conn.send_request(&x::InternAtom {
+            only_if_exists: false,
+            name: format!("WM_S{}", screen_number).as_bytes(),
+        });
+        let manager_cookie = conn.send_request(&x::InternAtom {
+            only_if_exists: false,
+            name: b"MANAGER",
+        });
+        let atom = conn.wait_for_reply(atom_cookie)?.atom();
+        let manager = conn.wait_for_reply(manager_cookie)?.atom();
+
+        let previous_owner = conn
+            .wait_for_reply(conn.send_request(&x::GetSelectionOwner { selection: atom }))?
+            .owner();
+
+        if !previous_owner.is_none() {
+            if !replace {
+                return Err(StartError::AlreadyRunning { can_replace: true });
+            }
+
+            // Find out when the previous window manager is done.
+            conn.send_request(&x::ChangeWindowAttributes {
+                window: previous_owner,
+                value_list: &[x::Cw::EventMask(x::EventMask::STRUCTURE_NOTIFY)],
+            });
+        }
+
+        let window = conn.generate_id();
+
+        conn.send_request(&x::CreateWindow {
+            depth: x::COPY_FROM_PARENT as u8,
+            wid: window,
+            parent: root,
+            x: -1,
+            y: -1,
+            width: 1,
+            height: 1,
+            border_width: 0,
+            class: x::WindowClass::InputOnly,
+            visual: x::Window::none().resource_id(),
+            value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE)],
+        });
+
+        let time = timestamp(conn, window, atom)?;
+
+        conn.send_request(&x::ChangeWindowAttributes {
+            window,
+            value_list: &[x::Cw::EventMask(x::EventMask::NO_EVENT)],
+        });
+        conn.send_request(&x::SetSelectionOwner {
+            owner: window,
+            selection: atom,
+            time,
+        });
+
+        let owner = conn
+            .wait_for_reply(conn.send_request(&x::GetSelectionOwner { selection: atom }))?
+            .owner();
+
+        if owner != window {
+            conn.send_request(&x::DestroyWindow { window });
+            conn.flush()?;
+
+            return Err(StartError::AlreadyRunning { can_replace: false });
+        }
+
+        if !previous_owner.is_none() {
+            println!("Replacing the running window manager");
+            wait_for_destroy(conn, previous_owner)?;
+        }
+
+        let cookie = conn.send_request_checked(&x::ChangeWindowAttributes {
+            window: root,
+            value_list: &[x::Cw::EventMask(x::EventMask::SUBSTRUCTURE_REDIRECT)],
+        });
+
+        if conn.check_request(cookie).is_err() {
+            // Give up the selection, as this instance does not manage the
+            // screen after all.
+            conn.send_request(&x::DestroyWindow { window });
+            conn.flush()?;
+
+            return Err(StartError::AlreadyRunning { can_replace: false });
+        }
+
+        // Let clients know that there is a new window manager.
+        let event = x::ClientMessageEvent::new(
+            root,
+            manager,
+            x::ClientMessageData::Data32([time, atom.resource_id(), window.resource_id(), 0, 0]),
+        );
+
+        conn.send_request(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(root),
+            event_mask: x::EventMask::STRUCTURE_NOTIFY,
+            event: &event,
+        });
+
+        Ok(Self { window, atom })
+    }
+
+    /// Returns the window that owns the selection.
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    /// Returns the `WM_S<n>` atom.
+    pub fn atom(&self) -> x::Atom {
+        self.atom
+    }
+}
+
+/// Returns the current server time, from a zero-length change to a property
+/// of the given window, which must select `PROPERTY_CHANGE`.
+fn timestamp(
+    conn: &xcb::Connection,
+    window: x::Window,
+    property: x::Atom,
+) -> xcb::Result<x::Timestamp> {
+    conn.send_request(&x::ChangeProperty {
+        mode: x::PropMode::Append,
+        window,
+        property,
+        r#type: x::ATOM_STRING,
+        data: &[] as &[u8],
+    });
+    conn.flush()?;
+
+    loop {
+        match conn.wait_for_event() {
+            Ok(xcb::Event::X(x::Event::PropertyNotify(property_notify)))
+                if property_notify.window() == window =>
+            {
+                return Ok(property_notify.time());
+            }
+            // E.g., from selecting events on a previous owner that is gone.
+            Ok(_) | Err(xcb::Error::Protocol(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Waits until the given window is destroyed, giving up after
+/// [`REPLACE_TIMEOUT`].
+fn wait_for_destroy(conn: &xcb::Connection, window: x::Window) -> xcb::Result<()> {
+    let deadline = Instant::now() + REPLACE_TIMEOUT;
+
+    conn.flush()?;
+
+    while Instant::now() < deadline {
+        match conn.poll_for_event() {
+            Ok(Some(xcb::Event::X(x::Event::DestroyNotify(destroy_notify))))
+                if destroy_notify.window() == window =>
+            {
+                return Ok(());
+            }
+            Ok(Some(_)) | Err(xcb::Error::Protocol(_)) => {}
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(err) => return Err(err),
+        }
+    }
+
+    println!("The running window manager did not exit in time");
+
+    Ok(())
+}
+
+/// An error that keeps `ravenwm` from managing a screen.
+#[derive(Debug)]
+pub enum StartError {
+    /// Another window manager manages the screen.
+    AlreadyRunning {
+        /// Whether it can be replaced with `--replace`.
+        can_replace: bool,
+    },
+
+    X(xcb::Error),
+}
+
+impl From<xcb::Error> for StartError {
+    fn from(err: xcb::Error) -> Self {
+        Self::X(err)
+    }
+}
+
+impl From<xcb::ConnError> for StartError {
+    fn from(err: xcb::ConnError) -> Self {
+        Self::X(err.into())
+    }
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyRunning { can_replace: true } => write!(
+                f,
+                "Another window manager is running; start with --replace to replace it"
+            ),
+            Self::AlreadyRunning { can_replace: false } => write!(
+                f,
+                "Another window manager is running and cannot be replaced"
+            ),
+            Self::X(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for StartError {}
@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, process};
+
+use serde::{Deserialize, Serialize};
+
+use crate::layout::LayoutState;
+
+/// What `ravenwm` hands over to the new instance when it restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// The index of the focused monitor.
+    pub focused_monitor: usize,
+
+    /// The index of the workspace shown on every monitor.
+    pub monitors: Vec<usize>,
+
+    /// The ID of the focused window.
+    pub focused_window: Option<u32>,
+
+    pub workspaces: Vec<WorkspaceState>,
+}
+
+/// A workspace in the [`State`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceState {
+    /// The name of the layout.
+    pub layout: String,
+
+    /// The IDs of the windows that were focused, the most recent one last.
+    pub focus_history: Vec<u32>,
+
+    /// The settings of the tiled layouts.
+    pub layouts: LayoutState<u32>,
+
+    /// The clients, in the order they were added.
+    pub clients: Vec<ClientState>,
+}
+
+/// A client in the [`State`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientState {
+    pub window: u32,
+
+    /// The floating area, including the border.
+    pub floating_rect: (i16, i16, u16, u16),
+    pub is_floating: bool,
+    #[serde(default)]
+    pub is_fullscreen: bool,
+
+    /// The geometry and border width the window had before it was managed.
+    pub original_geometry: (i16, i16, u16, u16),
+    pub original_border_width: u16,
+}
+
+impl State {
+    /// Returns the path of the file the state is handed over in.
+    pub fn path() -> PathBuf {
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .filter(|runtime_dir| !runtime_dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir);
+
+        runtime_dir.join(format!("ravenwm-{}.toml", process::id()))
+    }
+
+    /// Loads the state from the given file and removes the file.
+    pub fn take(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        fs::remove_file(path)?;
+
+        toml::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Saves the state to the given file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        // Going through a `toml::Value` puts tables after plain values, which
+        // the split tree of the BSP layout mixes.
+        let contents = toml::Value::try_from(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        fs::write(path, contents.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{Node, Orientation};
+
+    #[test]
+    fn state_survives_a_round_trip() {
+        let client = |window, is_floating, is_fullscreen| ClientState {
+            window,
+            floating_rect: (-10, 20, 300, 200),
+            is_floating,
+            is_fullscreen,
+            original_geometry: (0, 0, 296, 196),
+            original_border_width: 1,
+        };
+        let bsp = Node::Split {
+            orientation: Orientation::Horizontal,
+            ratio: 0.25,
+            first: Box::new(Node::Split {
+                orientation: Orientation::Vertical,
+                ratio: 0.75,
+                first: Box::new(Node::Leaf(7)),
+                second: Box::new(Node::Leaf(8)),
+            }),
+            second: Box::new(Node::Leaf(9)),
+        };
+        let state = State {
+            focused_monitor: 1,
+            monitors: vec![0, 2],
+            focused_window: Some(7),
+            workspaces: vec![WorkspaceState {
+                layout: "bsp".into(),
+                focus_history: vec![7],
+                layouts: LayoutState {
+                    master_count: Some(2),
+                    master_ratio: Some(0.6),
+                    bsp: Some(bsp.clone()),
+                },
+                clients: vec![
+                    client(7, true, false),
+                    client(8, false, true),
+                    client(9, false, false),
+                ],
+            }],
+        };
+
+        let path = env::temp_dir().join(format!("ravenwm-test-{}.toml", process::id()));
+        state.save(&path).unwrap();
+        let state = State::take(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(state.monitors, [0, 2]);
+        assert_eq!(state.focused_window, Some(7));
+
+        let workspace = &state.workspaces[0];
+        assert_eq!(workspace.layout, "bsp");
+        assert_eq!(workspace.layouts.master_count, Some(2));
+        assert_eq!(workspace.layouts.master_ratio, Some(0.6));
+        assert_eq!(workspace.layouts.bsp, Some(bsp));
+        assert_eq!(workspace.clients[0].floating_rect, (-10, 20, 300, 200));
+        assert!(workspace.clients[0].is_floating);
+        assert!(workspace.clients[1].is_fullscreen);
+        assert!(!workspace.clients[2].is_fullscreen);
+    }
+}
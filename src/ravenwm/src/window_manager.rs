@@ -0,0 +1,2699 @@
+use std::os::unix::prelude::{AsRawFd, RawFd};
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+use hex_color::HexColor;
+use nix::fcntl::{fcntl, FcntlArg};
+use nix::sys::select::{select, FdSet};
+use nix::sys::time::{TimeVal, TimeValLike};
+use ravenwm_core::ipc;
+use xcb::{randr, x, Xid};
+
+use crate::atoms::Atoms;
+use crate::client::XClient;
+use crate::config::{Binding, Config, ConfigWatcher, Rule, RuleEffects};
+use crate::drag::{Drag, MoveResize};
+use crate::ewmh::{self, ClientList};
+use crate::geometry::Rectangle;
+use crate::keyboard::{Chord, KeyBindings, Keymap};
+use crate::layout::Placement;
+use crate::monitor::{self, Monitor};
+use crate::plumage::Color;
+use crate::selection::WmSelection;
+use crate::state::{ClientState, State, WorkspaceState};
+use crate::workspace::{self, Workspace};
+
+/// The event mask for the root window.
+const ROOT_EVENT_MASK: x::EventMask = x::EventMask::SUBSTRUCTURE_REDIRECT
+    .union(x::EventMask::SUBSTRUCTURE_NOTIFY)
+    .union(x::EventMask::STRUCTURE_NOTIFY)
+    .union(x::EventMask::BUTTON_PRESS);
+
+/// Why [`WindowManager::run`] returned.
+#[derive(Debug)]
+pub enum Exit {
+    Quit,
+    /// Restart in place, loading the state from the given file.
+    Restart(PathBuf),
+}
+
+/// The window manager.
+pub struct WindowManager {
+    conn: xcb::Connection,
+    ipc_server: ipc::Server,
+    atoms: Atoms,
+    root: x::Window,
+    screen_rect: Rectangle,
+    meta_window: x::Window,
+    /// The `WM_S<n>` selection, owned by the meta window.
+    wm_selection: x::Atom,
+    placement: Placement,
+    default_layout: String,
+    master_count: usize,
+    master_ratio: f32,
+    workspaces: Vec<Workspace>,
+    monitors: Vec<Monitor>,
+    focused_monitor: usize,
+    focused_client: Option<x::Window>,
+    client_list: ClientList,
+    drag: Option<Drag>,
+    close_timeout: Duration,
+    pending_closes: Vec<(x::Window, Instant)>,
+    window_border_width: u32,
+    window_border_color: Color,
+    window_gap_width: u32,
+    keymap: Keymap,
+    key_bindings: KeyBindings,
+    rules: Vec<Rule>,
+    config_watcher: Option<ConfigWatcher>,
+    children: Vec<Child>,
+    is_running: bool,
+    restart_state_path: Option<PathBuf>,
+}
+
+impl WindowManager {
+    /// Creates a new [`WindowManager`] that manages the given screen, taking
+    /// over the windows in the given state from before a restart.
+    ///
+    /// The given [`WmSelection`] must have been acquired for the screen.
+    pub fn new(
+        conn: xcb::Connection,
+        screen_number: i32,
+        selection: WmSelection,
+        ipc_server: ipc::Server,
+        config: Config,
+        state: State,
+    ) -> xcb::Result<Self> {
+        let setup = conn.get_setup();
+        let screen = setup.roots().nth(screen_number as usize).unwrap();
+
+        let root = screen.root();
+        let screen_rect = Rectangle::new(0, 0, screen.width_in_pixels(), screen.height_in_pixels());
+
+        conn.send_request(&x::ChangeWindowAttributes {
+            window: root,
+            value_list: &[x::Cw::EventMask(ROOT_EVENT_MASK)],
+        });
+
+        let meta_window = selection.window();
+
+        let atoms = Atoms::intern(&conn)?;
+        let keymap = Keymap::fetch(&conn)?;
+
+        if monitor::has_randr(&conn) {
+            conn.send_request(&randr::SelectInput {
+                window: root,
+                enable: randr::NotifyMask::SCREEN_CHANGE
+                    | randr::NotifyMask::CRTC_CHANGE
+                    | randr::NotifyMask::OUTPUT_CHANGE,
+            });
+        }
+
+        let monitors: Vec<_> = monitor::fetch_monitors(&conn, root, screen_rect)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, rect))| Monitor::new(name, rect, index))
+            .collect();
+
+        let config_watcher = Config::path().and_then(|path| match ConfigWatcher::new(&path) {
+            Ok(config_watcher) => Some(config_watcher),
+            Err(err) => {
+                println!("Not watching {} for changes: {}", path.display(), err);
+                None
+            }
+        });
+
+        let border_color = config.border_color;
+
+        let mut window_manager = Self {
+            conn,
+            ipc_server,
+            atoms,
+            root,
+            screen_rect,
+            meta_window,
+            wm_selection: selection.atom(),
+            placement: config.placement,
+            default_layout: config.layout,
+            master_count: config.master_count,
+            master_ratio: config.master_ratio,
+            workspaces: Vec::new(),
+            monitors,
+            focused_monitor: 0,
+            focused_client: None,
+            client_list: ClientList::default(),
+            drag: None,
+            close_timeout: Duration::from_millis(config.close_timeout),
+            pending_closes: Vec::new(),
+            window_border_width: config.border_width,
+            window_border_color: Color::rgb(border_color.r, border_color.g, border_color.b),
+            window_gap_width: config.gap_width,
+            keymap,
+            key_bindings: KeyBindings::default(),
+            rules: config.rules,
+            config_watcher,
+            children: Vec::new(),
+            is_running: true,
+            restart_state_path: None,
+        };
+
+        window_manager.set_supported();
+        window_manager.set_bindings(config.bindings);
+        window_manager.set_workspace_names(config.workspaces);
+        window_manager.update_client_list();
+        window_manager.update_active_window();
+        window_manager.adopt_windows(state)?;
+
+        Ok(window_manager)
+    }
+
+    /// Runs the window manager until it is told to quit or restart.
+    pub fn run(&mut self) -> xcb::Result<Exit> {
+        let ipc_fd = self.ipc_server.as_raw_fd();
+        let xcb_fd = self.conn.as_raw_fd();
+        let config_fd = self
+            .config_watcher
+            .as_ref()
+            .map(|config_watcher| config_watcher.as_raw_fd());
+
+        let mut descriptors = FdSet::new();
+        let mut write_descriptors = FdSet::new();
+
+        while self.is_running {
+            self.conn.flush()?;
+
+            self.reap_children();
+
+            descriptors.clear();
+            descriptors.insert(ipc_fd);
+            descriptors.insert(xcb_fd);
+
+            if let Some(config_fd) = config_fd {
+                descriptors.insert(config_fd);
+            }
+
+            for connection_fd in self.ipc_server.connection_fds() {
+                descriptors.insert(connection_fd);
+            }
+
+            write_descriptors.clear();
+
+            for connection_fd in self.ipc_server.pending_write_fds() {
+                write_descriptors.insert(connection_fd);
+            }
+
+            // Wake up in time to kill the clients of windows that did not
+            // close.
+            let now = Instant::now();
+            let mut timeout = self
+                .pending_closes
+                .iter()
+                .map(|(_, deadline)| deadline.saturating_duration_since(now))
+                .min()
+                .map(|timeout| TimeVal::milliseconds(timeout.as_millis() as i64 + 1));
+
+            let ready_fds = match select(
+                None,
+                Some(&mut descriptors),
+                Some(&mut write_descriptors),
+                None,
+                timeout.as_mut(),
+            ) {
+                Ok(ready_fds) => ready_fds,
+                Err(nix::errno::Errno::EINTR) => continue,
+                Err(err) => {
+                    println!("Failed to wait for file descriptors: {}", err);
+
+                    // Drop the connections that are no longer valid, and give
+                    // up if it is one of our own descriptors instead.
+                    let invalid_fds: Vec<_> = self
+                        .ipc_server
+                        .connection_fds()
+                        .filter(|connection_fd| fcntl(*connection_fd, FcntlArg::F_GETFD).is_err())
+                        .collect();
+
+                    if invalid_fds.is_empty() {
+                        return Err(xcb::Error::Connection(xcb::ConnError::Connection));
+                    }
+
+                    for connection_fd in invalid_fds {
+                        self.ipc_server.disconnect(connection_fd);
+                    }
+
+                    continue;
+                }
+            };
+
+            self.kill_unresponsive_clients()?;
+
+            if ready_fds > 0 {
+                let writable_connection_fds: Vec<_> = self
+                    .ipc_server
+                    .pending_write_fds()
+                    .filter(|connection_fd| write_descriptors.contains(*connection_fd))
+                    .collect();
+
+                for connection_fd in writable_connection_fds {
+                    self.ipc_server.flush(connection_fd);
+                }
+
+                let ready_connection_fds: Vec<_> = self
+                    .ipc_server
+                    .connection_fds()
+                    .filter(|connection_fd| descriptors.contains(*connection_fd))
+                    .collect();
+
+                'requests: for connection_fd in ready_connection_fds {
+                    for request in self.ipc_server.receive(connection_fd) {
+                        println!("Message: {:?}", request.message());
+
+                        let reply =
+                            match self.handle_message(request.message(), Some(connection_fd)) {
+                                Ok(reply) => reply,
+                                Err(xcb::Error::Protocol(err)) => {
+                                    println!("X error: {:?}", err);
+                                    ipc::Reply::error(format!("X error: {:?}", err))
+                                }
+                                Err(err) => return Err(err),
+                            };
+
+                        self.ipc_server.reply(connection_fd, &reply);
+
+                        if !self.is_running {
+                            break 'requests;
+                        }
+                    }
+                }
+
+                if descriptors.contains(ipc_fd) {
+                    self.ipc_server.accept();
+                }
+
+                let config_has_changed = config_fd.is_some_and(|config_fd| {
+                    descriptors.contains(config_fd)
+                        && self
+                            .config_watcher
+                            .as_mut()
+                            .is_some_and(|config_watcher| config_watcher.has_changed())
+                });
+
+                if config_has_changed {
+                    println!("Config file changed");
+
+                    if let ipc::Reply::Error { reason } = self.reload()? {
+                        println!("{}", reason);
+                    }
+                }
+
+                if descriptors.contains(xcb_fd) {
+                    loop {
+                        // Requests on windows that were destroyed in the
+                        // meantime fail, which is nothing to stop for.
+                        let event = match self.conn.poll_for_event() {
+                            Ok(Some(event)) => event,
+                            Ok(None) => break,
+                            Err(xcb::Error::Protocol(err)) => {
+                                println!("X error: {:?}", err);
+                                continue;
+                            }
+                            Err(err) => return Err(err),
+                        };
+
+                        println!("Received event {:?}", event);
+
+                        match event {
+                            xcb::Event::X(event) => {
+                                if let Err(err) = self.handle_event(event) {
+                                    match err {
+                                        xcb::Error::Protocol(err) => println!("X error: {:?}", err),
+                                        err => return Err(err),
+                                    }
+                                }
+                            }
+                            xcb::Event::RandR(randr::Event::ScreenChangeNotify(screen_change)) => {
+                                self.screen_rect = Rectangle::new(
+                                    0,
+                                    0,
+                                    screen_change.width(),
+                                    screen_change.height(),
+                                );
+                                self.update_monitors()?;
+                            }
+                            xcb::Event::RandR(randr::Event::Notify(_)) => self.update_monitors()?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        self.shutdown()?;
+
+        Ok(match self.restart_state_path.take() {
+            Some(state_path) => Exit::Restart(state_path),
+            None => Exit::Quit,
+        })
+    }
+
+    fn handle_message(
+        &mut self,
+        message: &ipc::Message,
+        connection_fd: Option<RawFd>,
+    ) -> xcb::Result<ipc::Reply> {
+        let reply = match *message {
+            ipc::Message::Quit => {
+                println!("Quit");
+                self.ipc_server.broadcast(&ipc::Event::Quit);
+                self.is_running = false;
+
+                ipc::Reply::Ok
+            }
+            ipc::Message::Restart => {
+                let state_path = State::path();
+
+                match self.state().save(&state_path) {
+                    Ok(()) => {
+                        println!("Restarting");
+                        self.restart_state_path = Some(state_path);
+                        self.is_running = false;
+
+                        ipc::Reply::Ok
+                    }
+                    Err(err) => ipc::Reply::error(format!(
+                        "Failed to save the state to {}: {}",
+                        state_path.display(),
+                        err
+                    )),
+                }
+            }
+            ipc::Message::CloseWindow => {
+                if let Some(currently_focused_client) = self.focused_client {
+                    self.close_window(currently_focused_client)?;
+
+                    ipc::Reply::Ok
+                } else {
+                    ipc::Reply::error("No focused window")
+                }
+            }
+            ipc::Message::KillWindow => {
+                if let Some(currently_focused_client) = self.focused_client {
+                    self.kill_window(currently_focused_client);
+
+                    ipc::Reply::Ok
+                } else {
+                    ipc::Reply::error("No focused window")
+                }
+            }
+            ipc::Message::MoveWindow { x, y } => match self.focused_client {
+                Some(focused_window) if self.workspace().is_tiled(focused_window) => {
+                    ipc::Reply::error("Tiled windows cannot be moved")
+                }
+                Some(focused_window) if self.workspace().is_fullscreen(focused_window) => {
+                    ipc::Reply::error("Fullscreen windows cannot be moved")
+                }
+                Some(focused_window) => {
+                    if let Some(client) = self.workspace_mut().client_mut(focused_window) {
+                        let mut floating_rect = client.floating_rect();
+                        floating_rect.x = x as i16;
+                        floating_rect.y = y as i16;
+                        client.set_floating_rect(floating_rect);
+                    }
+
+                    self.conn.send_request(&x::ConfigureWindow {
+                        window: focused_window,
+                        value_list: &[x::ConfigWindow::X(x as i32), x::ConfigWindow::Y(y as i32)],
+                    });
+
+                    ipc::Reply::Ok
+                }
+                None => ipc::Reply::error("No focused window"),
+            },
+            ipc::Message::SetBorderWidth { width } => {
+                self.set_border_width(width)?;
+
+                ipc::Reply::Ok
+            }
+            ipc::Message::SetBorderColor { color } => {
+                self.set_border_color(Color::rgb(color.r, color.g, color.b));
+
+                ipc::Reply::Ok
+            }
+            ipc::Message::Subscribe { ref events } => match connection_fd {
+                Some(connection_fd) => {
+                    self.ipc_server.subscribe(connection_fd, events);
+
+                    ipc::Reply::Ok
+                }
+                None => ipc::Reply::error("Subscribing requires an IPC connection"),
+            },
+            ipc::Message::Query(query) => match self.query(query) {
+                Ok(data) => ipc::Reply::Data(data),
+                Err(err) => ipc::Reply::error(format!("Query failed: {}", err)),
+            },
+            ipc::Message::Bind {
+                ref chord,
+                ref action,
+            } => match chord.parse::<Chord>() {
+                Ok(chord) if self.keymap.keycodes(chord.keysym()).is_empty() => {
+                    ipc::Reply::error(format!("No key on the keyboard produces '{}'", chord))
+                }
+                Ok(chord) => {
+                    println!("Binding '{}' to {:?}", chord, action);
+                    self.key_bindings.bind(chord, action.clone());
+                    self.key_bindings.grab(&self.conn, self.root, &self.keymap);
+
+                    ipc::Reply::Ok
+                }
+                Err(err) => ipc::Reply::error(err),
+            },
+            ipc::Message::Unbind { ref chord } => match chord.parse::<Chord>() {
+                Ok(chord) if self.key_bindings.unbind(chord) => {
+                    println!("Unbinding '{}'", chord);
+                    self.key_bindings.grab(&self.conn, self.root, &self.keymap);
+
+                    ipc::Reply::Ok
+                }
+                Ok(chord) => ipc::Reply::error(format!("'{}' is not bound", chord)),
+                Err(err) => ipc::Reply::error(err),
+            },
+            ipc::Message::Reload => self.reload()?,
+            ipc::Message::SetMasterRatio { .. }
+            | ipc::Message::SetMasterCount { .. }
+            | ipc::Message::Bsp(_) => {
+                let focused = self.focused_client;
+                let layout = self.workspace_mut().layout_mut();
+
+                match layout.handle_message(message, focused) {
+                    Some(Ok(())) => {
+                        self.arrange();
+
+                        ipc::Reply::Ok
+                    }
+                    Some(Err(err)) => ipc::Reply::error(err),
+                    None => ipc::Reply::error(format!(
+                        "The {} layout does not support '{}'",
+                        layout.name(),
+                        message.command()
+                    )),
+                }
+            }
+            ipc::Message::ToggleFloating => match self.focused_client {
+                Some(focused_window) => match self.workspace_mut().client_mut(focused_window) {
+                    Some(client) => {
+                        client.set_floating(!client.is_floating());
+
+                        println!(
+                            "Window {:?} is {}",
+                            focused_window,
+                            if client.is_floating() {
+                                "floating"
+                            } else {
+                                "tiled"
+                            }
+                        );
+
+                        self.arrange();
+
+                        ipc::Reply::Ok
+                    }
+                    None => ipc::Reply::error("The focused window is not managed"),
+                },
+                None => ipc::Reply::error("No focused window"),
+            },
+            ipc::Message::SetLayout { ref name } => match self.set_layout(name) {
+                Ok(()) => {
+                    self.arrange();
+
+                    ipc::Reply::Ok
+                }
+                Err(err) => ipc::Reply::error(err),
+            },
+            ipc::Message::Workspace { number } => match self.workspace_index(number) {
+                Ok(index) => {
+                    self.switch_workspace(index);
+
+                    ipc::Reply::Ok
+                }
+                Err(err) => ipc::Reply::error(err),
+            },
+            ipc::Message::FocusMonitor { number } => match self.monitor_index(number) {
+                Ok(index) => {
+                    self.set_focused_monitor(index);
+                    self.focus(self.workspace().last_focused());
+
+                    ipc::Reply::Ok
+                }
+                Err(err) => ipc::Reply::error(err),
+            },
+            ipc::Message::SendToMonitor { number } => {
+                match (self.monitor_index(number), self.focused_client) {
+                    (Err(err), _) => ipc::Reply::error(err),
+                    (Ok(_), None) => ipc::Reply::error("No focused window"),
+                    (Ok(index), Some(focused_window)) => {
+                        let workspace = self.monitors[index].workspace();
+
+                        if self.send_to_workspace(focused_window, workspace) {
+                            ipc::Reply::Ok
+                        } else {
+                            ipc::Reply::error("The focused window is not managed")
+                        }
+                    }
+                }
+            }
+            ipc::Message::SendTo { number } => {
+                match (self.workspace_index(number), self.focused_client) {
+                    (Err(err), _) => ipc::Reply::error(err),
+                    (Ok(_), None) => ipc::Reply::error("No focused window"),
+                    (Ok(index), Some(focused_window)) => {
+                        if self.send_to_workspace(focused_window, index) {
+                            ipc::Reply::Ok
+                        } else {
+                            ipc::Reply::error("The focused window is not managed")
+                        }
+                    }
+                }
+            }
+        };
+
+        Ok(reply)
+    }
+
+    fn handle_event(&mut self, event: x::Event) -> xcb::Result<()> {
+        match event {
+            x::Event::MapRequest(map_request) => {
+                println!("XCB_MAP_REQUEST");
+
+                let window = map_request.window();
+
+                if let Some(index) = self.workspace_of(window) {
+                    // Windows on hidden workspaces are mapped when their
+                    // workspace is shown.
+                    if self.is_visible(index) {
+                        self.set_client_mapped(window, true);
+                    }
+
+                    return Ok(());
+                }
+
+                let effects = self.rule_effects(window);
+
+                if !effects.manage {
+                    println!("Not managing window: {:?}", window);
+                    self.conn.send_request(&x::MapWindow { window });
+
+                    return Ok(());
+                }
+
+                let workspace = self.current_workspace();
+                let floating_rect = match self.place(window, workspace)? {
+                    Some(floating_rect) => floating_rect,
+                    None => return Ok(()),
+                };
+
+                self.manage(window, workspace, floating_rect)?;
+
+                if effects.focus {
+                    self.focus(Some(window));
+                }
+            }
+            x::Event::UnmapNotify(unmap_notify) => {
+                let window = unmap_notify.window();
+
+                let is_expected = self
+                    .workspace_of(window)
+                    .and_then(|index| self.workspaces[index].client_mut(window))
+                    .map(|client| client.take_expected_unmap());
+
+                match is_expected {
+                    Some(true) => {}
+                    Some(false) => {
+                        // The client withdrew the window.
+                        println!("Window {:?} was unmapped", window);
+
+                        for property in [self.atoms.net_wm_desktop, self.atoms.net_wm_state] {
+                            self.conn
+                                .send_request(&x::DeleteProperty { window, property });
+                        }
+
+                        self.forget_window(window);
+                    }
+                    None => self.forget_focus(window),
+                }
+            }
+            x::Event::DestroyNotify(destroy_notify) => {
+                let window = destroy_notify.window();
+
+                if self.workspace_of(window).is_some() {
+                    println!("Window {:?} was destroyed", window);
+
+                    self.forget_window(window);
+                } else {
+                    self.forget_focus(window);
+                }
+            }
+            x::Event::ConfigureRequest(configure_request) => {
+                println!("XCB_CONFIGURE_REQUEST");
+
+                let window = configure_request.window();
+                let value_mask = configure_request.value_mask();
+                let border_width = self.window_border_width as u16;
+
+                if let Some(index) = self.workspace_of(window) {
+                    // Managed windows only get to change their floating area.
+                    let is_visible_and_floating =
+                        self.is_visible(index) && self.workspaces[index].is_floating(window);
+                    let client = self.workspaces[index].client_mut(window).unwrap();
+                    let mut floating_rect = client.floating_rect();
+
+                    if value_mask.contains(x::ConfigWindowMask::X) {
+                        floating_rect.x = configure_request.x();
+                    }
+
+                    if value_mask.contains(x::ConfigWindowMask::Y) {
+                        floating_rect.y = configure_request.y();
+                    }
+
+                    if value_mask.contains(x::ConfigWindowMask::WIDTH) {
+                        floating_rect.width = configure_request.width() + 2 * border_width;
+                    }
+
+                    if value_mask.contains(x::ConfigWindowMask::HEIGHT) {
+                        floating_rect.height = configure_request.height() + 2 * border_width;
+                    }
+
+                    client.set_floating_rect(floating_rect);
+
+                    if is_visible_and_floating {
+                        self.configure_client(window, floating_rect);
+
+                        if value_mask.contains(x::ConfigWindowMask::STACK_MODE) {
+                            self.restack(window, configure_request.stack_mode());
+                        }
+                    } else {
+                        self.send_configure_notify(window)?;
+                    }
+                } else {
+                    self.forward_configure_request(&configure_request);
+                }
+
+                self.conn.send_request(&x::ChangeWindowAttributes {
+                    window: configure_request.window(),
+                    value_list: &[x::Cw::EventMask(
+                        x::EventMask::PROPERTY_CHANGE.union(x::EventMask::FOCUS_CHANGE),
+                    )],
+                });
+            }
+            x::Event::KeyPress(key_press) => {
+                let action = self
+                    .key_bindings
+                    .find(&self.keymap, key_press.detail(), key_press.state())
+                    .cloned();
+
+                if let Some(action) = action {
+                    self.run_action(&action)?;
+                }
+            }
+            x::Event::MappingNotify(mapping_notify)
+                if mapping_notify.request() == x::Mapping::Keyboard =>
+            {
+                println!("Keyboard mapping changed");
+
+                self.keymap = Keymap::fetch(&self.conn)?;
+                self.key_bindings.grab(&self.conn, self.root, &self.keymap);
+            }
+            x::Event::MotionNotify(motion_notify) => {
+                println!("XCB_MOTION_NOTIFY");
+
+                if let Some(drag) = &self.drag {
+                    let window = drag.window();
+                    let rect = drag.rect_at((motion_notify.root_x(), motion_notify.root_y()));
+
+                    self.set_floating_rect(window, rect);
+                }
+            }
+            x::Event::ButtonRelease(_button_release) => self.end_drag(false),
+            x::Event::ClientMessage(client_message) => {
+                if let x::ClientMessageData::Data32(data) = client_message.data() {
+                    self.handle_client_message(
+                        client_message.window(),
+                        client_message.r#type(),
+                        data,
+                    )?;
+                }
+            }
+            x::Event::SelectionClear(selection_clear)
+                if selection_clear.selection() == self.wm_selection =>
+            {
+                println!("Replaced by another window manager");
+                self.is_running = false;
+            }
+            x::Event::ButtonPress(button_press) => {
+                println!("Mouse button '{}' pressed", button_press.detail());
+
+                if button_press.detail() == 0x1 {
+                    let child_window = button_press.child();
+
+                    println!("Child window: {:?}", child_window);
+
+                    if !child_window.is_none() {
+                        println!("Focusing window: {:?}", child_window);
+                        self.focus(Some(child_window));
+                    } else if let Some(monitor) =
+                        self.monitor_at(button_press.root_x(), button_press.root_y())
+                    {
+                        self.set_focused_monitor(monitor);
+                        self.focus(self.workspace().last_focused());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Handles a client message that an application or a pager sent to the
+    /// root window.
+    fn handle_client_message(
+        &mut self,
+        window: x::Window,
+        r#type: x::Atom,
+        data: [u32; 5],
+    ) -> xcb::Result<()> {
+        if r#type == self.atoms.net_current_desktop {
+            let index = data[0] as usize;
+
+            if index < self.workspaces.len() {
+                self.switch_workspace(index);
+            } else {
+                println!("There is no desktop {}", index);
+            }
+
+            return Ok(());
+        }
+
+        // The other messages are about managed windows.
+        let index = match self.workspace_of(window) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        if r#type == self.atoms.net_active_window {
+            if !self.is_visible(index) {
+                self.switch_workspace(index);
+            }
+
+            self.focus(Some(window));
+        } else if r#type == self.atoms.net_close_window {
+            self.close_window(window)?;
+        } else if r#type == self.atoms.net_wm_moveresize {
+            let pointer = (data[0] as i16, data[1] as i16);
+
+            match MoveResize::from_direction(data[2]) {
+                Some(MoveResize::Cancel) => self.end_drag(true),
+                Some(move_resize) => self.start_drag(window, move_resize, pointer)?,
+                None => println!(
+                    "Not moving or resizing window {:?} with the keyboard",
+                    window
+                ),
+            }
+        } else if r#type == self.atoms.net_wm_state {
+            let fullscreen = self.atoms.net_wm_state_fullscreen.resource_id();
+
+            if data[1] == fullscreen || data[2] == fullscreen {
+                // The action is 0 to remove, 1 to add and 2 to toggle the state.
+                let is_fullscreen = match data[0] {
+                    0 => false,
+                    1 => true,
+                    _ => !self.workspaces[index].is_fullscreen(window),
+                };
+
+                self.set_fullscreen(window, is_fullscreen)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts moving or resizing the given window with the pointer from the
+    /// given position, if it floats on a shown workspace.
+    fn start_drag(
+        &mut self,
+        window: x::Window,
+        move_resize: MoveResize,
+        pointer: (i16, i16),
+    ) -> xcb::Result<()> {
+        let floating_rect = match self.workspace_of(window) {
+            Some(index) if self.is_visible(index) && self.workspaces[index].is_floating(window) => {
+                self.workspaces[index]
+                    .clients()
+                    .iter()
+                    .find(|client| client.window() == window)
+                    .map(|client| client.floating_rect())
+            }
+            _ => None,
+        };
+
+        let floating_rect = match floating_rect {
+            Some(floating_rect) if self.drag.is_none() => floating_rect,
+            _ => {
+                println!("Not moving or resizing window {:?}", window);
+                return Ok(());
+            }
+        };
+
+        let cookie = self.conn.send_request(&x::GrabPointer {
+            owner_events: false,
+            grab_window: self.root,
+            event_mask: x::EventMask::BUTTON_RELEASE | x::EventMask::POINTER_MOTION,
+            pointer_mode: x::GrabMode::Async,
+            keyboard_mode: x::GrabMode::Async,
+            confine_to: x::Window::none(),
+            cursor: x::Cursor::none(),
+            time: x::CURRENT_TIME,
+        });
+        let status = self.conn.wait_for_reply(cookie)?.status();
+
+        if status != x::GrabStatus::Success {
+            println!("Could not grab the pointer: {:?}", status);
+            return Ok(());
+        }
+
+        println!("{:?} window {:?}", move_resize, window);
+
+        self.drag = Some(Drag::new(window, move_resize, pointer, floating_rect));
+        self.focus(Some(window));
+
+        Ok(())
+    }
+
+    /// Stops moving or resizing a window with the pointer, moving it back if
+    /// it is cancelled.
+    fn end_drag(&mut self, is_cancelled: bool) {
+        if let Some(drag) = self.drag.take() {
+            self.conn.send_request(&x::UngrabPointer {
+                time: x::CURRENT_TIME,
+            });
+
+            if is_cancelled {
+                self.set_floating_rect(drag.window(), drag.original_rect());
+            }
+        }
+    }
+
+    /// Sets the floating area of the given window, moving it there if it
+    /// floats on a shown workspace.
+    fn set_floating_rect(&mut self, window: x::Window, rect: Rectangle) {
+        if let Some(index) = self.workspace_of(window) {
+            if let Some(client) = self.workspaces[index].client_mut(window) {
+                client.set_floating_rect(rect);
+            }
+
+            if self.is_visible(index) && self.workspaces[index].is_floating(window) {
+                self.configure_client(window, rect);
+            }
+        }
+    }
+
+    /// Makes the given window cover its whole monitor, or puts it back.
+    fn set_fullscreen(&mut self, window: x::Window, is_fullscreen: bool) -> xcb::Result<()> {
+        if let Some(client) = self
+            .workspace_of(window)
+            .and_then(|index| self.workspaces[index].client_mut(window))
+        {
+            client.set_fullscreen(is_fullscreen);
+        }
+
+        println!(
+            "Window {:?} is {}",
+            window,
+            if is_fullscreen {
+                "fullscreen"
+            } else {
+                "not fullscreen"
+            }
+        );
+
+        let fullscreen = self.atoms.net_wm_state_fullscreen;
+        let mut state = self.atom_list(window, self.atoms.net_wm_state)?;
+        state.retain(|atom| *atom != fullscreen);
+
+        if is_fullscreen {
+            state.push(fullscreen);
+        }
+
+        self.set_property(window, self.atoms.net_wm_state, x::ATOM_ATOM, &state);
+
+        self.arrange();
+
+        Ok(())
+    }
+
+    /// Returns the value of a property of the given window that holds a list
+    /// of atoms, like `_NET_WM_STATE` or `WM_PROTOCOLS`.
+    fn atom_list(&self, window: x::Window, property: x::Atom) -> xcb::Result<Vec<x::Atom>> {
+        let cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property,
+            r#type: x::ATOM_ATOM,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let reply = match unless_gone(self.conn.wait_for_reply(cookie))? {
+            Some(reply) => reply,
+            None => return Ok(Vec::new()),
+        };
+
+        if reply.r#type() != x::ATOM_ATOM {
+            return Ok(Vec::new());
+        }
+
+        Ok(reply.value::<x::Atom>().to_vec())
+    }
+
+    /// Reloads the configuration file and applies it.
+    ///
+    /// Nothing is applied if the file is invalid.
+    fn reload(&mut self) -> xcb::Result<ipc::Reply> {
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(err) => {
+                self.ipc_server.broadcast(&ipc::Event::ConfigReloaded {
+                    error: Some(err.to_string()),
+                });
+
+                return Ok(ipc::Reply::error(err.to_string()));
+            }
+        };
+
+        println!("Reloading config");
+
+        self.apply_config(config)?;
+
+        self.ipc_server
+            .broadcast(&ipc::Event::ConfigReloaded { error: None });
+
+        Ok(ipc::Reply::Ok)
+    }
+
+    /// Applies the given configuration to the existing windows.
+    fn apply_config(&mut self, config: Config) -> xcb::Result<()> {
+        if config.border_width != self.window_border_width {
+            self.set_border_width(config.border_width)?;
+        }
+
+        let border_color = Color::rgb(
+            config.border_color.r,
+            config.border_color.g,
+            config.border_color.b,
+        );
+        if border_color != self.window_border_color {
+            self.set_border_color(border_color);
+        }
+
+        self.window_gap_width = config.gap_width;
+        self.close_timeout = Duration::from_millis(config.close_timeout);
+        self.placement = config.placement;
+
+        let previous_layout_name = self.workspace().layout_name();
+
+        // Only settings that changed in the file are applied to the
+        // workspaces, so that the layouts and ratios changed at runtime
+        // survive reloads.
+        let layout_changed = config.layout != self.default_layout;
+        let master_count_changed = config.master_count != self.master_count;
+        let master_ratio_changed = config.master_ratio != self.master_ratio;
+
+        self.default_layout = config.layout;
+        self.master_count = config.master_count;
+        self.master_ratio = config.master_ratio;
+        self.set_workspace_names(config.workspaces);
+
+        for workspace in &mut self.workspaces {
+            for layout in workspace.layouts_mut() {
+                if master_ratio_changed {
+                    layout.handle_message(
+                        &ipc::Message::SetMasterRatio {
+                            ratio: ipc::Adjustment::Set(config.master_ratio),
+                        },
+                        None,
+                    );
+                }
+
+                if master_count_changed {
+                    layout.handle_message(
+                        &ipc::Message::SetMasterCount {
+                            count: ipc::Adjustment::Set(config.master_count as i32),
+                        },
+                        None,
+                    );
+                }
+            }
+
+            if layout_changed {
+                if let Err(err) = workspace.set_layout(&self.default_layout) {
+                    println!("{}", err);
+                }
+            }
+        }
+
+        if self.workspace().layout_name() != previous_layout_name {
+            self.ipc_server.broadcast(&ipc::Event::LayoutChanged {
+                layout: self.workspace().layout_name().to_string(),
+            });
+        }
+
+        self.arrange();
+
+        self.set_bindings(config.bindings);
+
+        self.rules = config.rules;
+
+        let unmanaged_windows: Vec<_> = self
+            .clients()
+            .map(|client| client.window())
+            .filter(|window| !self.rule_effects(*window).manage)
+            .collect();
+
+        for window in unmanaged_windows {
+            self.unmanage(window);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces all key bindings with the given ones.
+    fn set_bindings(&mut self, bindings: Vec<Binding>) {
+        self.key_bindings = KeyBindings::default();
+
+        for binding in bindings {
+            if self.keymap.keycodes(binding.chord.keysym()).is_empty() {
+                println!("No key on the keyboard produces '{}'", binding.chord);
+            }
+
+            self.key_bindings.bind(binding.chord, binding.action);
+        }
+
+        self.key_bindings.grab(&self.conn, self.root, &self.keymap);
+    }
+
+    /// Returns the attributes and geometry of the given window, or `None` if
+    /// it is gone.
+    fn window_attributes(
+        &self,
+        window: x::Window,
+    ) -> xcb::Result<Option<(x::GetWindowAttributesReply, x::GetGeometryReply)>> {
+        let attributes_cookie = self.conn.send_request(&x::GetWindowAttributes { window });
+        let geometry_cookie = self.conn.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(window),
+        });
+
+        match (
+            self.conn.wait_for_reply(attributes_cookie),
+            self.conn.wait_for_reply(geometry_cookie),
+        ) {
+            (Ok(attributes), Ok(geometry)) => Ok(Some((attributes, geometry))),
+            (Err(xcb::Error::Protocol(_)), _) | (_, Err(xcb::Error::Protocol(_))) => Ok(None),
+            (Err(err), _) | (_, Err(err)) => Err(err),
+        }
+    }
+
+    /// Starts managing the given window on the given workspace, mapping it if
+    /// the workspace is shown.
+    fn manage(
+        &mut self,
+        window: x::Window,
+        workspace: usize,
+        floating_rect: Rectangle,
+    ) -> xcb::Result<()> {
+        let (attributes, geometry) = match self.window_attributes(window)? {
+            Some(attributes) => attributes,
+            None => return Ok(()),
+        };
+
+        let mut client = XClient::new(window, floating_rect);
+
+        client.set_original_geometry(
+            Rectangle::new(
+                geometry.x(),
+                geometry.y(),
+                geometry.width(),
+                geometry.height(),
+            ),
+            geometry.border_width(),
+        );
+
+        // Windows can ask to start out fullscreen.
+        client.set_fullscreen(
+            self.atom_list(window, self.atoms.net_wm_state)?
+                .contains(&self.atoms.net_wm_state_fullscreen),
+        );
+        client.set_mapped(attributes.map_state() == x::MapState::Viewable);
+
+        self.workspaces[workspace].add(client);
+        self.client_list.add(window);
+        self.update_client_list();
+        self.update_client_desktop(window);
+        self.arrange();
+
+        self.conn.send_request(&x::ChangeWindowAttributes {
+            window,
+            value_list: &[x::Cw::BorderPixel(self.window_border_color.into())],
+        });
+
+        self.set_client_mapped(window, self.is_visible(workspace));
+
+        self.ipc_server.broadcast(&ipc::Event::WindowMapped {
+            window: window.resource_id(),
+        });
+
+        Ok(())
+    }
+
+    /// Manages the windows that were open before the window manager started,
+    /// e.g. under a previous window manager or before a restart.
+    ///
+    /// The windows in the given state from before a restart get back their
+    /// workspace and floating area. Other mapped windows go to the workspace
+    /// on the monitor they are on, and keep their geometry as their floating
+    /// area.
+    fn adopt_windows(&mut self, state: State) -> xcb::Result<()> {
+        self.restore_workspaces(&state);
+
+        let tree = self
+            .conn
+            .wait_for_reply(self.conn.send_request(&x::QueryTree { window: self.root }))?;
+        let children = tree.children();
+
+        // Restore the saved clients first, to keep them in order.
+        for (index, workspace_state) in state.workspaces.iter().enumerate() {
+            let workspace = index.min(self.workspaces.len() - 1);
+
+            for client_state in &workspace_state.clients {
+                let window = match children
+                    .iter()
+                    .find(|window| window.resource_id() == client_state.window)
+                {
+                    Some(&window) => window,
+                    None => continue,
+                };
+
+                let (x, y, width, height) = client_state.floating_rect;
+
+                println!("Restoring window: {:?}", window);
+
+                self.manage(window, workspace, Rectangle::new(x, y, width, height))?;
+
+                if let Some(client) = self.workspaces[workspace].client_mut(window) {
+                    let (x, y, width, height) = client_state.original_geometry;
+
+                    client.set_floating(client_state.is_floating);
+                    client.set_fullscreen(client_state.is_fullscreen);
+                    client.set_original_geometry(
+                        Rectangle::new(x, y, width, height),
+                        client_state.original_border_width,
+                    );
+                }
+            }
+
+            let focus_history: Vec<_> = workspace_state
+                .focus_history
+                .iter()
+                .filter_map(|id| {
+                    self.workspaces[workspace]
+                        .clients()
+                        .iter()
+                        .map(|client| client.window())
+                        .find(|window| window.resource_id() == *id)
+                })
+                .collect();
+
+            for window in focus_history {
+                self.workspaces[workspace].focus(window);
+            }
+
+            // Only now that its clients are back, or they would be dropped
+            // from the split tree of the BSP layout.
+            let windows: Vec<_> = self.workspaces[workspace]
+                .clients()
+                .iter()
+                .map(|client| client.window())
+                .collect();
+            let layouts = workspace_state.layouts.clone().filter_map(|id| {
+                windows
+                    .iter()
+                    .copied()
+                    .find(|window| window.resource_id() == id)
+            });
+
+            self.workspaces[workspace].restore_layouts(&layouts);
+        }
+
+        for &window in children {
+            if window == self.meta_window || self.workspace_of(window).is_some() {
+                continue;
+            }
+
+            // The window may be gone by now.
+            let (attributes, geometry) = match self.window_attributes(window)? {
+                Some(attributes) => attributes,
+                None => continue,
+            };
+
+            if attributes.override_redirect() || attributes.map_state() != x::MapState::Viewable {
+                continue;
+            }
+
+            if !self.rule_effects(window).manage {
+                println!("Not managing window: {:?}", window);
+                continue;
+            }
+
+            let border_width = self.window_border_width as u16;
+            let floating_rect = Rectangle::new(
+                geometry.x(),
+                geometry.y(),
+                geometry.width() + 2 * border_width,
+                geometry.height() + 2 * border_width,
+            );
+
+            let center = (
+                floating_rect.x + floating_rect.width as i16 / 2,
+                floating_rect.y + floating_rect.height as i16 / 2,
+            );
+            let workspace = match self.monitor_at(center.0, center.1) {
+                Some(monitor) => self.monitors[monitor].workspace(),
+                None => self.current_workspace(),
+            };
+
+            println!("Adopting window: {:?}", window);
+
+            self.manage(window, workspace, floating_rect)?;
+        }
+
+        self.arrange();
+
+        let focused_window = self
+            .workspace()
+            .clients()
+            .iter()
+            .map(|client| client.window())
+            .find(|window| Some(window.resource_id()) == state.focused_window);
+
+        self.focus(focused_window.or_else(|| self.workspace().last_focused()));
+
+        Ok(())
+    }
+
+    /// Restores the current layouts and the shown workspaces from the given
+    /// state.
+    ///
+    /// The shown workspaces are only restored if the monitors still fit.
+    fn restore_workspaces(&mut self, state: &State) {
+        for (workspace, workspace_state) in self.workspaces.iter_mut().zip(&state.workspaces) {
+            if let Err(err) = workspace.set_layout(&workspace_state.layout) {
+                println!("{}", err);
+            }
+        }
+
+        let shown = &state.monitors;
+        let fits = shown.len() == self.monitors.len()
+            && shown
+                .iter()
+                .all(|workspace| *workspace < self.workspaces.len())
+            && shown
+                .iter()
+                .enumerate()
+                .all(|(index, workspace)| !shown[..index].contains(workspace));
+
+        if fits {
+            for (monitor, workspace) in self.monitors.iter_mut().zip(shown) {
+                monitor.set_workspace(*workspace);
+            }
+
+            self.focused_monitor = state.focused_monitor.min(self.monitors.len() - 1);
+            self.update_current_desktop();
+        }
+    }
+
+    /// Returns the state to hand over to the new instance on restart.
+    fn state(&self) -> State {
+        let rect_tuple = |rect: Rectangle| (rect.x, rect.y, rect.width, rect.height);
+
+        State {
+            focused_monitor: self.focused_monitor,
+            monitors: self
+                .monitors
+                .iter()
+                .map(|monitor| monitor.workspace())
+                .collect(),
+            focused_window: self.focused_client.map(|window| window.resource_id()),
+            workspaces: self
+                .workspaces
+                .iter()
+                .map(|workspace| WorkspaceState {
+                    layout: workspace.layout_name().to_string(),
+                    layouts: workspace
+                        .layout_state()
+                        .filter_map(|window| Some(window.resource_id())),
+                    focus_history: workspace
+                        .focus_history()
+                        .iter()
+                        .map(|window| window.resource_id())
+                        .collect(),
+                    clients: workspace
+                        .clients()
+                        .iter()
+                        .map(|client| {
+                            let (original_geometry, original_border_width) =
+                                client.original_geometry();
+
+                            ClientState {
+                                window: client.window().resource_id(),
+                                floating_rect: rect_tuple(client.floating_rect()),
+                                is_floating: client.is_floating(),
+                                is_fullscreen: client.is_fullscreen(),
+                                original_geometry: rect_tuple(original_geometry),
+                                original_border_width,
+                            }
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Stops managing the given window, leaving it where it is.
+    ///
+    /// Windows on hidden workspaces are shown again.
+    fn unmanage(&mut self, window: x::Window) {
+        println!("Not managing window anymore: {:?}", window);
+
+        if let Some(index) = self.workspace_of(window) {
+            self.workspaces[index].remove(window);
+
+            if !self.is_visible(index) {
+                self.conn.send_request(&x::MapWindow { window });
+            }
+        }
+
+        self.client_list.remove(window);
+        self.update_client_list();
+
+        for property in [self.atoms.net_wm_desktop, self.atoms.net_wm_state] {
+            self.conn
+                .send_request(&x::DeleteProperty { window, property });
+        }
+
+        self.conn.send_request(&x::ConfigureWindow {
+            window,
+            value_list: &[x::ConfigWindow::BorderWidth(0)],
+        });
+
+        self.arrange();
+
+        if self.focused_client == Some(window) {
+            self.focus(self.workspace().last_focused());
+        }
+    }
+
+    /// Asks the given window to close with `WM_DELETE_WINDOW`.
+    ///
+    /// The client is killed if the window does not support that, or does not
+    /// close within the close timeout.
+    fn close_window(&mut self, window: x::Window) -> xcb::Result<()> {
+        let protocols = self.atom_list(window, self.atoms.wm_protocols)?;
+
+        if !protocols.contains(&self.atoms.wm_delete_window) {
+            self.kill_window(window);
+
+            return Ok(());
+        }
+
+        println!("Sending WM_DELETE_WINDOW event");
+        self.send_protocol(window, self.atoms.wm_delete_window);
+
+        let is_pending = self
+            .pending_closes
+            .iter()
+            .any(|(pending_window, _)| *pending_window == window);
+
+        if !self.close_timeout.is_zero() && !is_pending {
+            self.pending_closes
+                .push((window, Instant::now() + self.close_timeout));
+        }
+
+        Ok(())
+    }
+
+    /// Closes the given window by killing its client.
+    fn kill_window(&mut self, window: x::Window) {
+        println!("Killing client: {:?}", window);
+        self.conn.send_request(&x::KillClient {
+            resource: window.resource_id(),
+        });
+
+        self.forget_window(window);
+    }
+
+    /// Kills the clients of windows that were asked to close and did not
+    /// within the close timeout.
+    fn kill_unresponsive_clients(&mut self) -> xcb::Result<()> {
+        let now = Instant::now();
+        let (expired, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_closes)
+            .into_iter()
+            .partition(|(_, deadline)| *deadline <= now);
+
+        self.pending_closes = pending;
+
+        for (window, _) in expired {
+            // The window may have closed in the meantime.
+            let cookie = self.conn.send_request(&x::GetWindowAttributes { window });
+
+            match self.conn.wait_for_reply(cookie) {
+                Ok(_) => {
+                    println!("Window {:?} did not close in time", window);
+                    self.kill_window(window);
+                }
+                Err(xcb::Error::Protocol(_)) => self.forget_window(window),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forgets about the given window after it is gone.
+    fn forget_window(&mut self, window: x::Window) {
+        self.pending_closes
+            .retain(|(pending_window, _)| *pending_window != window);
+
+        if let Some(index) = self.workspace_of(window) {
+            self.workspaces[index].remove(window);
+        }
+
+        self.client_list.remove(window);
+        self.update_client_list();
+
+        if self.drag.as_ref().map(|drag| drag.window()) == Some(window) {
+            self.end_drag(false);
+        }
+
+        self.ipc_server.broadcast(&ipc::Event::WindowUnmapped {
+            window: window.resource_id(),
+        });
+
+        self.arrange();
+
+        if self.focused_client == Some(window) {
+            self.focus(self.workspace().last_focused());
+        }
+    }
+
+    /// Focuses another window if the given unmanaged window was focused and
+    /// is gone.
+    fn forget_focus(&mut self, window: x::Window) {
+        if self.focused_client == Some(window) {
+            self.focus(self.workspace().last_focused());
+        }
+    }
+
+    /// Sets the border width of all windows.
+    fn set_border_width(&mut self, width: u32) -> xcb::Result<()> {
+        let border_width_delta = width as i32 - self.window_border_width as i32;
+
+        self.window_border_width = width;
+
+        for client in self.clients() {
+            let window_geometry = {
+                let cookie = self.conn.send_request(&x::GetGeometry {
+                    drawable: x::Drawable::Window(client.window()),
+                });
+
+                match unless_gone(self.conn.wait_for_reply(cookie))? {
+                    Some(window_geometry) => window_geometry,
+                    None => continue,
+                }
+            };
+
+            let width = window_geometry.width() as i32 - 2 * border_width_delta;
+            let height = window_geometry.height() as i32 - 2 * border_width_delta;
+
+            self.conn.send_request(&x::ConfigureWindow {
+                window: client.window(),
+                value_list: &[
+                    x::ConfigWindow::BorderWidth(self.window_border_width),
+                    x::ConfigWindow::Width(width.max(1) as u32),
+                    x::ConfigWindow::Height(height.max(1) as u32),
+                ],
+            });
+        }
+
+        self.ipc_server.broadcast(&self.border_changed_event());
+
+        Ok(())
+    }
+
+    /// Sets the border color of all windows.
+    fn set_border_color(&mut self, color: Color) {
+        self.window_border_color = color;
+
+        for client in self.clients() {
+            self.conn.send_request(&x::ChangeWindowAttributes {
+                window: client.window(),
+                value_list: &[x::Cw::BorderPixel(self.window_border_color.into())],
+            });
+        }
+
+        self.ipc_server.broadcast(&self.border_changed_event());
+    }
+
+    /// Returns the index of the workspace shown on the focused monitor.
+    fn current_workspace(&self) -> usize {
+        self.monitors[self.focused_monitor].workspace()
+    }
+
+    /// Returns the workspace shown on the focused monitor.
+    fn workspace(&self) -> &Workspace {
+        &self.workspaces[self.current_workspace()]
+    }
+
+    /// Returns the workspace shown on the focused monitor.
+    fn workspace_mut(&mut self) -> &mut Workspace {
+        let index = self.current_workspace();
+
+        &mut self.workspaces[index]
+    }
+
+    /// Returns the index of the workspace the given window is on, if it is
+    /// managed.
+    fn workspace_of(&self, window: x::Window) -> Option<usize> {
+        workspace::position_of(&self.workspaces, window)
+    }
+
+    /// Returns the index of the workspace with the given number, counting from
+    /// 1.
+    fn workspace_index(&self, number: u32) -> Result<usize, String> {
+        match number as usize {
+            number @ 1.. if number <= self.workspaces.len() => Ok(number - 1),
+            _ => Err(format!("There is no workspace {}", number)),
+        }
+    }
+
+    /// Returns the index of the monitor with the given number, counting from
+    /// 1.
+    fn monitor_index(&self, number: u32) -> Result<usize, String> {
+        match number as usize {
+            number @ 1.. if number <= self.monitors.len() => Ok(number - 1),
+            _ => Err(format!("There is no monitor {}", number)),
+        }
+    }
+
+    /// Returns the index of the monitor showing the given workspace, if it is
+    /// shown.
+    fn monitor_showing(&self, workspace: usize) -> Option<usize> {
+        self.monitors
+            .iter()
+            .position(|monitor| monitor.workspace() == workspace)
+    }
+
+    /// Returns the index of the monitor containing the given point.
+    fn monitor_at(&self, x: i16, y: i16) -> Option<usize> {
+        self.monitors.iter().position(|monitor| {
+            let rect = monitor.rect();
+
+            (rect.left()..rect.right()).contains(&x) && (rect.top()..rect.bottom()).contains(&y)
+        })
+    }
+
+    /// Returns whether the given workspace is shown on a monitor.
+    fn is_visible(&self, workspace: usize) -> bool {
+        self.monitor_showing(workspace).is_some()
+    }
+
+    /// Returns the clients on all workspaces.
+    fn clients(&self) -> impl Iterator<Item = &XClient> {
+        self.workspaces
+            .iter()
+            .flat_map(|workspace| workspace.clients())
+    }
+
+    /// Maps or unmaps all clients on the given workspace.
+    fn set_workspace_mapped(&mut self, workspace: usize, is_mapped: bool) {
+        let windows: Vec<_> = self.workspaces[workspace]
+            .clients()
+            .iter()
+            .map(|client| client.window())
+            .collect();
+
+        for window in windows {
+            self.set_client_mapped(window, is_mapped);
+        }
+    }
+
+    /// Maps or unmaps the given managed window, remembering the unmaps that
+    /// the window manager caused.
+    fn set_client_mapped(&mut self, window: x::Window, is_mapped: bool) {
+        let client = match self
+            .workspace_of(window)
+            .and_then(|index| self.workspaces[index].client_mut(window))
+        {
+            Some(client) => client,
+            None => return,
+        };
+
+        if is_mapped {
+            client.set_mapped(true);
+            self.conn.send_request(&x::MapWindow { window });
+        } else if client.is_mapped() {
+            client.set_mapped(false);
+            self.conn.send_request(&x::UnmapWindow { window });
+        }
+    }
+
+    /// Focuses the monitor with the given index, without changing the focused
+    /// window.
+    fn set_focused_monitor(&mut self, monitor: usize) {
+        if monitor == self.focused_monitor {
+            return;
+        }
+
+        self.focused_monitor = monitor;
+
+        println!("Focusing monitor {}", self.monitors[monitor].name());
+
+        self.update_current_desktop();
+
+        self.ipc_server.broadcast(&ipc::Event::MonitorFocused {
+            monitor: monitor as u32 + 1,
+            name: self.monitors[monitor].name().to_string(),
+        });
+    }
+
+    /// Shows the workspace with the given index on the focused monitor.
+    ///
+    /// If the workspace is already shown on another monitor, the two
+    /// monitors swap workspaces.
+    fn switch_workspace(&mut self, index: usize) {
+        let previous_workspace = self.current_workspace();
+
+        if index == previous_workspace {
+            return;
+        }
+
+        let previous_layout_name = self.workspace().layout_name();
+
+        match self.monitor_showing(index) {
+            Some(other_monitor) => {
+                self.monitors[other_monitor].set_workspace(previous_workspace);
+                self.monitors[self.focused_monitor].set_workspace(index);
+
+                self.arrange();
+            }
+            None => {
+                self.monitors[self.focused_monitor].set_workspace(index);
+
+                self.arrange();
+
+                self.set_workspace_mapped(index, true);
+                self.set_workspace_mapped(previous_workspace, false);
+            }
+        }
+
+        println!("Switching to workspace {}", self.workspace().name());
+
+        self.update_current_desktop();
+
+        self.ipc_server.broadcast(&ipc::Event::WorkspaceChanged {
+            workspace: index as u32 + 1,
+            name: self.workspace().name().to_string(),
+        });
+
+        if self.workspace().layout_name() != previous_layout_name {
+            self.ipc_server.broadcast(&ipc::Event::LayoutChanged {
+                layout: self.workspace().layout_name().to_string(),
+            });
+        }
+
+        self.focus(self.workspace().last_focused());
+    }
+
+    /// Moves the given window from the current workspace to the workspace
+    /// with the given index.
+    ///
+    /// The window is hidden unless the workspace is shown on another monitor,
+    /// in which case its floating area moves along to that monitor. Returns
+    /// `false` if the window is not on the current workspace.
+    fn send_to_workspace(&mut self, window: x::Window, index: usize) -> bool {
+        if index == self.current_workspace() {
+            return self.workspace().contains(window);
+        }
+
+        let mut client = match self.workspace_mut().remove(window) {
+            Some(client) => client,
+            None => return false,
+        };
+
+        println!(
+            "Sending window {:?} to workspace {}",
+            window,
+            self.workspaces[index].name()
+        );
+
+        match self.monitor_showing(index) {
+            Some(monitor) => {
+                let from = self.monitors[self.focused_monitor].rect();
+                let to = self.monitors[monitor].rect();
+
+                let mut floating_rect = client.floating_rect();
+                floating_rect.x += to.x - from.x;
+                floating_rect.y += to.y - from.y;
+                client.set_floating_rect(floating_rect);
+            }
+            None => {
+                client.set_mapped(false);
+                self.conn.send_request(&x::UnmapWindow { window });
+            }
+        }
+
+        self.workspaces[index].add(client);
+        self.update_client_desktop(window);
+
+        self.arrange();
+
+        if self.focused_client == Some(window) {
+            self.focus(self.workspace().last_focused());
+        }
+
+        true
+    }
+
+    /// Renames the workspaces, adding or removing workspaces to match the
+    /// given names.
+    ///
+    /// There is always at least one workspace for every monitor, named after
+    /// its number if needed. The clients of removed workspaces move to the
+    /// last remaining one.
+    fn set_workspace_names(&mut self, mut names: Vec<String>) {
+        while names.len() < self.monitors.len() {
+            names.push((names.len() + 1).to_string());
+        }
+
+        if names.len() < self.workspaces.len() {
+            let last_workspace = names.len() - 1;
+
+            for monitor in 0..self.monitors.len() {
+                if self.monitors[monitor].workspace() > last_workspace {
+                    let hidden_workspace = (0..=last_workspace)
+                        .find(|workspace| !self.is_visible(*workspace))
+                        .unwrap();
+
+                    self.monitors[monitor].set_workspace(hidden_workspace);
+                    self.set_workspace_mapped(hidden_workspace, true);
+                }
+            }
+
+            let clients: Vec<_> = self
+                .workspaces
+                .drain(last_workspace + 1..)
+                .flat_map(|mut workspace| workspace.take_clients())
+                .collect();
+
+            for client in clients {
+                self.workspaces[last_workspace].add(client);
+            }
+
+            self.set_workspace_mapped(last_workspace, self.is_visible(last_workspace));
+        }
+
+        for (index, name) in names.into_iter().enumerate() {
+            match self.workspaces.get_mut(index) {
+                Some(workspace) => workspace.set_name(name),
+                None => {
+                    let mut workspace = Workspace::new(name, self.master_count, self.master_ratio);
+
+                    if let Err(err) = workspace.set_layout(&self.default_layout) {
+                        println!("{}", err);
+                    }
+
+                    self.workspaces.push(workspace);
+                }
+            }
+        }
+
+        self.update_desktops();
+    }
+
+    /// Fetches the monitors again after they changed, keeping the workspaces
+    /// they show where possible.
+    fn update_monitors(&mut self) -> xcb::Result<()> {
+        let monitors = monitor::fetch_monitors(&self.conn, self.root, self.screen_rect)?;
+
+        let is_unchanged = monitors.len() == self.monitors.len()
+            && monitors
+                .iter()
+                .zip(&self.monitors)
+                .all(|((name, rect), monitor)| name == monitor.name() && *rect == monitor.rect());
+
+        if is_unchanged {
+            return Ok(());
+        }
+
+        println!("Monitors changed: {:?}", monitors);
+
+        let names: Vec<_> = self
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.name().to_string())
+            .collect();
+        let previously_shown: Vec<_> = self
+            .monitors
+            .iter()
+            .map(|monitor| monitor.workspace())
+            .collect();
+
+        // Add workspaces for new monitors before they need to show them.
+        self.monitors = monitors
+            .iter()
+            .enumerate()
+            .map(|(index, (name, rect))| Monitor::new(name.clone(), *rect, index))
+            .collect();
+        self.set_workspace_names(names);
+
+        let mut shown = previously_shown.clone();
+        shown.truncate(monitors.len());
+
+        while shown.len() < monitors.len() {
+            let hidden_workspace = (0..self.workspaces.len())
+                .find(|workspace| !shown.contains(workspace))
+                .unwrap();
+
+            shown.push(hidden_workspace);
+        }
+
+        for (monitor, workspace) in self.monitors.iter_mut().zip(&shown) {
+            monitor.set_workspace(*workspace);
+        }
+
+        for workspace in 0..self.workspaces.len() {
+            let was_shown = previously_shown.contains(&workspace);
+            let is_shown = shown.contains(&workspace);
+
+            if was_shown != is_shown {
+                self.set_workspace_mapped(workspace, is_shown);
+            }
+        }
+
+        self.focused_monitor = self.focused_monitor.min(self.monitors.len() - 1);
+        self.update_current_desktop();
+
+        self.arrange();
+        self.focus(self.workspace().last_focused());
+
+        Ok(())
+    }
+
+    /// Switches the current workspace to the layout with the given name.
+    ///
+    /// Does not arrange the windows in the new layout.
+    fn set_layout(&mut self, name: &str) -> Result<(), String> {
+        let previous_layout_name = self.workspace().layout_name();
+
+        self.workspace_mut().set_layout(name)?;
+
+        let layout_name = self.workspace().layout_name();
+
+        if layout_name != previous_layout_name {
+            println!("Switching to the {} layout", layout_name);
+
+            self.ipc_server.broadcast(&ipc::Event::LayoutChanged {
+                layout: layout_name.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Arranges the windows on every monitor according to the layout of the
+    /// workspace it shows.
+    ///
+    /// Floating windows, and every window in the stacking layout, are moved
+    /// back to their floating area.
+    fn arrange(&mut self) {
+        let gap = self.window_gap_width as u16;
+        let focused = self.focused_client;
+
+        for monitor in 0..self.monitors.len() {
+            let area = self.usable_area(monitor);
+            let monitor_rect = self.monitors[monitor].rect();
+            let workspace = self.monitors[monitor].workspace();
+
+            for (window, rect) in
+                self.workspaces[workspace].arrange(area, monitor_rect, gap, focused)
+            {
+                self.configure_client(window, rect);
+            }
+
+            self.restack_workspace(workspace);
+        }
+    }
+
+    /// Stacks the windows of the given workspace.
+    ///
+    /// Floating windows always stay above the tiled ones, and fullscreen
+    /// windows above both. If the tiled windows overlap, the focused one is
+    /// raised above the others.
+    fn restack_workspace(&mut self, workspace: usize) {
+        let focused_tiled_window = self
+            .focused_client
+            .filter(|_| self.workspaces[workspace].is_overlapping())
+            .filter(|window| self.workspaces[workspace].is_tiled(*window));
+
+        let clients = self.workspaces[workspace].clients();
+        let floating_windows = clients
+            .iter()
+            .filter(|client| client.is_floating() && !client.is_fullscreen())
+            .filter(|_| !self.workspaces[workspace].is_stacking());
+        let fullscreen_windows = clients.iter().filter(|client| client.is_fullscreen());
+        let raised_windows: Vec<_> = focused_tiled_window
+            .into_iter()
+            .chain(
+                floating_windows
+                    .chain(fullscreen_windows)
+                    .map(|client| client.window()),
+            )
+            .collect();
+
+        for window in raised_windows {
+            self.restack(window, x::StackMode::Above);
+        }
+    }
+
+    /// Returns the area of the given monitor that windows are placed in.
+    fn usable_area(&self, monitor: usize) -> Rectangle {
+        let gap = self.window_gap_width as i16;
+
+        let mut area = self.monitors[monitor].rect();
+        area.deflate(gap, gap);
+        area
+    }
+
+    /// Returns the floating area of a new window on the given workspace,
+    /// placed according to the placement policy, or `None` if the window is
+    /// gone.
+    fn place(&self, window: x::Window, workspace: usize) -> xcb::Result<Option<Rectangle>> {
+        let geometry_cookie = self.conn.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(window),
+        });
+        let pointer_cookie = self
+            .conn
+            .send_request(&x::QueryPointer { window: self.root });
+
+        let pointer = self.conn.wait_for_reply(pointer_cookie)?;
+        let geometry = match unless_gone(self.conn.wait_for_reply(geometry_cookie))? {
+            Some(geometry) => geometry,
+            None => return Ok(None),
+        };
+
+        let border_width = self.window_border_width as u16;
+        let others: Vec<_> = self.workspaces[workspace]
+            .clients()
+            .iter()
+            .filter(|client| client.is_floating())
+            .map(|client| client.floating_rect())
+            .collect();
+
+        // Place the window on the monitor showing its workspace, or on the
+        // one under the pointer if the workspace is hidden.
+        let monitor = self
+            .monitor_showing(workspace)
+            .or_else(|| self.monitor_at(pointer.root_x(), pointer.root_y()))
+            .unwrap_or(self.focused_monitor);
+
+        Ok(Some(self.placement.place(
+            geometry.width() + 2 * border_width,
+            geometry.height() + 2 * border_width,
+            self.usable_area(monitor),
+            (pointer.root_x(), pointer.root_y()),
+            &others,
+        )))
+    }
+
+    /// Moves the given window into the given area, including its border.
+    fn configure_client(&self, window: x::Window, rect: Rectangle) {
+        // Fullscreen windows have no border.
+        let is_fullscreen = self
+            .workspace_of(window)
+            .is_some_and(|index| self.workspaces[index].is_fullscreen(window));
+        let border_width = if is_fullscreen {
+            0
+        } else {
+            self.window_border_width
+        };
+
+        self.conn.send_request(&x::ConfigureWindow {
+            window,
+            value_list: &[
+                x::ConfigWindow::X(rect.x as i32),
+                x::ConfigWindow::Y(rect.y as i32),
+                x::ConfigWindow::Width((rect.width as i32 - 2 * border_width as i32).max(1) as u32),
+                x::ConfigWindow::Height(
+                    (rect.height as i32 - 2 * border_width as i32).max(1) as u32
+                ),
+                x::ConfigWindow::BorderWidth(border_width),
+            ],
+        });
+    }
+
+    /// Restacks the given window, keeping track of the stacking order of
+    /// managed windows.
+    fn restack(&mut self, window: x::Window, stack_mode: x::StackMode) {
+        self.conn.send_request(&x::ConfigureWindow {
+            window,
+            value_list: &[x::ConfigWindow::StackMode(stack_mode)],
+        });
+
+        let is_changed = match stack_mode {
+            x::StackMode::Above => self.client_list.raise(window),
+            x::StackMode::Below => self.client_list.lower(window),
+            _ => false,
+        };
+
+        if is_changed {
+            self.update_client_list();
+        }
+    }
+
+    /// Grants a configure request of a window that is not managed.
+    fn forward_configure_request(&self, configure_request: &x::ConfigureRequestEvent) {
+        let mut values = Vec::with_capacity(7);
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::X)
+        {
+            values.push(x::ConfigWindow::X(configure_request.x() as i32));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::Y)
+        {
+            values.push(x::ConfigWindow::Y(configure_request.y() as i32));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::WIDTH)
+        {
+            values.push(x::ConfigWindow::Width(configure_request.width() as u32));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::HEIGHT)
+        {
+            values.push(x::ConfigWindow::Height(configure_request.height() as u32));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::BORDER_WIDTH)
+        {
+            values.push(x::ConfigWindow::BorderWidth(
+                configure_request.border_width() as u32,
+            ));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::SIBLING)
+        {
+            values.push(x::ConfigWindow::Sibling(configure_request.sibling()));
+        }
+
+        if configure_request
+            .value_mask()
+            .contains(x::ConfigWindowMask::STACK_MODE)
+        {
+            values.push(x::ConfigWindow::StackMode(configure_request.stack_mode()));
+        }
+
+        self.conn.send_request(&x::ConfigureWindow {
+            window: configure_request.window(),
+            value_list: values.as_slice(),
+        });
+    }
+
+    /// Tells the given window its current geometry, in place of granting its
+    /// configure request.
+    fn send_configure_notify(&self, window: x::Window) -> xcb::Result<()> {
+        let geometry = {
+            let cookie = self.conn.send_request(&x::GetGeometry {
+                drawable: x::Drawable::Window(window),
+            });
+
+            match unless_gone(self.conn.wait_for_reply(cookie))? {
+                Some(geometry) => geometry,
+                None => return Ok(()),
+            }
+        };
+
+        let event = x::ConfigureNotifyEvent::new(
+            window,
+            window,
+            x::Window::none(),
+            geometry.x(),
+            geometry.y(),
+            geometry.width(),
+            geometry.height(),
+            geometry.border_width(),
+            false,
+        );
+
+        self.conn.send_request(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(window),
+            event_mask: x::EventMask::STRUCTURE_NOTIFY,
+            event: &event,
+        });
+
+        Ok(())
+    }
+
+    /// Focuses the given window, raising it unless it is tiled.
+    fn focus(&mut self, window: Option<x::Window>) {
+        self.focused_client = window;
+
+        if let Some(window) = window {
+            let monitor = self
+                .workspace_of(window)
+                .and_then(|workspace| self.monitor_showing(workspace));
+
+            if let Some(monitor) = monitor {
+                self.set_focused_monitor(monitor);
+            }
+
+            self.workspace_mut().focus(window);
+        }
+
+        if let Some(window) = window {
+            if !self.workspace().is_tiled(window) {
+                self.restack(window, x::StackMode::Above);
+            } else if self.workspace().is_overlapping() {
+                self.restack_workspace(self.current_workspace());
+            }
+        }
+
+        self.set_input_focus(window);
+        self.update_active_window();
+
+        self.ipc_server.broadcast(&ipc::Event::FocusChanged {
+            window: window.map(|window| window.resource_id()),
+        });
+    }
+
+    /// Gives the keyboard focus to the given window, or to the root window if
+    /// there is none.
+    ///
+    /// Windows that ask not to get the input focus in their `WM_HINTS` are
+    /// only sent `WM_TAKE_FOCUS`, if they support it.
+    fn set_input_focus(&self, window: Option<x::Window>) {
+        let window = match window {
+            Some(window) => window,
+            None => {
+                self.conn.send_request(&x::SetInputFocus {
+                    revert_to: x::InputFocus::PointerRoot,
+                    focus: self.root,
+                    time: x::CURRENT_TIME,
+                });
+
+                return;
+            }
+        };
+
+        let hints_cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property: x::ATOM_WM_HINTS,
+            r#type: x::ATOM_WM_HINTS,
+            long_offset: 0,
+            long_length: 9,
+        });
+        let protocols = self
+            .atom_list(window, self.atoms.wm_protocols)
+            .unwrap_or_default();
+
+        // The second field holds the input hint, if the first flag is set.
+        let accepts_input = match self.conn.wait_for_reply(hints_cookie) {
+            Ok(reply) if reply.r#type() == x::ATOM_WM_HINTS => {
+                let hints = reply.value::<u32>();
+                hints.len() < 2 || hints[0] & 1 == 0 || hints[1] != 0
+            }
+            _ => true,
+        };
+
+        // Focusing a window that is not viewable fails.
+        let is_mapped = self.workspace_of(window).is_some_and(|workspace| {
+            self.workspaces[workspace]
+                .clients()
+                .iter()
+                .any(|client| client.window() == window && client.is_mapped())
+        });
+
+        if accepts_input && is_mapped {
+            self.conn.send_request(&x::SetInputFocus {
+                revert_to: x::InputFocus::PointerRoot,
+                focus: window,
+                time: x::CURRENT_TIME,
+            });
+        }
+
+        if protocols.contains(&self.atoms.wm_take_focus) {
+            self.send_protocol(window, self.atoms.wm_take_focus);
+        }
+    }
+
+    /// Sends the given `WM_PROTOCOLS` message to the given window.
+    fn send_protocol(&self, window: x::Window, protocol: x::Atom) {
+        let event = x::ClientMessageEvent::new(
+            window,
+            self.atoms.wm_protocols,
+            x::ClientMessageData::Data32([protocol.resource_id(), x::CURRENT_TIME, 0, 0, 0]),
+        );
+
+        self.conn.send_request(&x::SendEvent {
+            propagate: false,
+            destination: x::SendEventDest::Window(window),
+            event_mask: x::EventMask::NO_EVENT,
+            event: &event,
+        });
+    }
+
+    /// Runs the action of a key binding.
+    fn run_action(&mut self, action: &ipc::Action) -> xcb::Result<()> {
+        match action {
+            ipc::Action::Message(message) => {
+                println!("Key binding: {:?}", message);
+
+                if let ipc::Reply::Error { reason } = self.handle_message(message, None)? {
+                    println!("Key binding failed: {}", reason);
+                }
+            }
+            ipc::Action::Spawn(command) => {
+                println!("Spawning: {}", command);
+
+                match Command::new("sh").arg("-c").arg(command).spawn() {
+                    Ok(child) => self.children.push(child),
+                    Err(err) => println!("Failed to spawn '{}': {}", command, err),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the spawned processes that have exited, so that they do not
+    /// linger as zombies.
+    fn reap_children(&mut self) {
+        self.children
+            .retain_mut(|child| !matches!(child.try_wait(), Ok(Some(_)) | Err(_)));
+    }
+
+    /// Returns the combined effect of the rules that match the given window.
+    fn rule_effects(&self, window: x::Window) -> RuleEffects {
+        if self.rules.is_empty() {
+            return RuleEffects::default();
+        }
+
+        match self.client_info(window) {
+            Ok(Some(client)) => RuleEffects::for_client(&self.rules, &client),
+            Ok(None) => RuleEffects::default(),
+            Err(err) => {
+                println!("Failed to match rules for {:?}: {:?}", window, err);
+                RuleEffects::default()
+            }
+        }
+    }
+
+    fn query(&self, query: ipc::Query) -> xcb::Result<ipc::Data> {
+        let data = match query {
+            ipc::Query::Clients => ipc::Data::Clients(
+                self.clients()
+                    .map(|client| self.client_info(client.window()))
+                    .filter_map(Result::transpose)
+                    .collect::<xcb::Result<_>>()?,
+            ),
+            ipc::Query::Focused => ipc::Data::Focused(
+                self.focused_client
+                    .map(|window| self.client_info(window))
+                    .transpose()?
+                    .flatten(),
+            ),
+            ipc::Query::Layout => ipc::Data::Layout {
+                name: self.workspace().layout_name().to_string(),
+            },
+            ipc::Query::Config => ipc::Data::Config(ipc::ConfigInfo {
+                border_width: self.window_border_width,
+                border_color: to_hex_color(self.window_border_color),
+                gap_width: self.window_gap_width,
+            }),
+            ipc::Query::Monitors => ipc::Data::Monitors(
+                self.monitors
+                    .iter()
+                    .enumerate()
+                    .map(|(index, monitor)| ipc::MonitorInfo {
+                        number: index as u32 + 1,
+                        name: monitor.name().to_string(),
+                        x: monitor.rect().x,
+                        y: monitor.rect().y,
+                        width: monitor.rect().width,
+                        height: monitor.rect().height,
+                        workspace: monitor.workspace() as u32 + 1,
+                        focused: index == self.focused_monitor,
+                    })
+                    .collect(),
+            ),
+            ipc::Query::Workspaces => ipc::Data::Workspaces(
+                self.workspaces
+                    .iter()
+                    .enumerate()
+                    .map(|(index, workspace)| ipc::WorkspaceInfo {
+                        number: index as u32 + 1,
+                        name: workspace.name().to_string(),
+                        layout: workspace.layout_name().to_string(),
+                        clients: workspace.clients().len() as u32,
+                        focused: index == self.current_workspace(),
+                        monitor: self
+                            .monitor_showing(index)
+                            .map(|monitor| monitor as u32 + 1),
+                    })
+                    .collect(),
+            ),
+        };
+
+        Ok(data)
+    }
+
+    /// Returns the [`ipc::ClientInfo`] for the given window.
+    fn client_info(&self, window: x::Window) -> xcb::Result<Option<ipc::ClientInfo>> {
+        let geometry_cookie = self.conn.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(window),
+        });
+        let class_cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property: x::ATOM_WM_CLASS,
+            r#type: x::ATOM_STRING,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let net_wm_name_cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property: self.atoms.net_wm_name,
+            r#type: self.atoms.utf8_string,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let wm_name_cookie = self.conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property: x::ATOM_WM_NAME,
+            r#type: x::ATOM_STRING,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+
+        let replies = (
+            unless_gone(self.conn.wait_for_reply(geometry_cookie))?,
+            unless_gone(self.conn.wait_for_reply(class_cookie))?,
+            unless_gone(self.conn.wait_for_reply(net_wm_name_cookie))?,
+            unless_gone(self.conn.wait_for_reply(wm_name_cookie))?,
+        );
+        let (geometry, class, net_wm_name, wm_name) = match replies {
+            (Some(geometry), Some(class), Some(net_wm_name), Some(wm_name)) => {
+                (geometry, class, net_wm_name, wm_name)
+            }
+            _ => return Ok(None),
+        };
+
+        // `WM_CLASS` holds two consecutive null-terminated strings: the instance and the class.
+        let mut class_parts = class
+            .value::<u8>()
+            .split(|byte| *byte == 0)
+            .map(|part| String::from_utf8_lossy(part).into_owned());
+        let instance = class_parts.next().filter(|part| !part.is_empty());
+        let class = class_parts.next().filter(|part| !part.is_empty());
+
+        let title = [net_wm_name.value::<u8>(), wm_name.value::<u8>()]
+            .into_iter()
+            .find(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned());
+
+        Ok(Some(ipc::ClientInfo {
+            window: window.resource_id(),
+            geometry: ipc::Geometry {
+                x: geometry.x(),
+                y: geometry.y(),
+                width: geometry.width(),
+                height: geometry.height(),
+                border_width: geometry.border_width(),
+            },
+            class,
+            instance,
+            title,
+            focused: self.focused_client == Some(window),
+            floating: self
+                .workspace_of(window)
+                .is_some_and(|index| self.workspaces[index].is_floating(window)),
+            workspace: self.workspace_of(window).map(|index| index as u32 + 1),
+        }))
+    }
+
+    /// Returns an [`ipc::Event::BorderChanged`] for the current border settings.
+    fn border_changed_event(&self) -> ipc::Event {
+        ipc::Event::BorderChanged {
+            width: self.window_border_width,
+            color: to_hex_color(self.window_border_color),
+        }
+    }
+
+    /// Replaces the value of a property of the given window.
+    fn set_property<P: x::PropEl>(
+        &self,
+        window: x::Window,
+        property: x::Atom,
+        r#type: x::Atom,
+        data: &[P],
+    ) {
+        self.conn.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property,
+            r#type,
+            data,
+        });
+    }
+
+    /// Announces an EWMH compliant window manager through the meta window.
+    fn set_supported(&self) {
+        for window in [self.root, self.meta_window] {
+            self.set_property(
+                window,
+                self.atoms.net_supporting_wm_check,
+                x::ATOM_WINDOW,
+                &[self.meta_window],
+            );
+        }
+
+        self.set_property(
+            self.meta_window,
+            self.atoms.net_wm_name,
+            self.atoms.utf8_string,
+            b"ravenwm",
+        );
+        self.set_property(
+            self.root,
+            self.atoms.net_supported,
+            x::ATOM_ATOM,
+            &ewmh::supported(&self.atoms),
+        );
+    }
+
+    /// Publishes the managed windows in `_NET_CLIENT_LIST` and
+    /// `_NET_CLIENT_LIST_STACKING`.
+    fn update_client_list(&self) {
+        self.set_property(
+            self.root,
+            self.atoms.net_client_list,
+            x::ATOM_WINDOW,
+            self.client_list.mapping_order(),
+        );
+        self.set_property(
+            self.root,
+            self.atoms.net_client_list_stacking,
+            x::ATOM_WINDOW,
+            self.client_list.stacking_order(),
+        );
+    }
+
+    /// Publishes the focused window in `_NET_ACTIVE_WINDOW`.
+    fn update_active_window(&self) {
+        self.set_property(
+            self.root,
+            self.atoms.net_active_window,
+            x::ATOM_WINDOW,
+            &[self.focused_client.unwrap_or_else(x::Window::none)],
+        );
+    }
+
+    /// Publishes the workspaces as EWMH desktops, along with the desktop of
+    /// every client.
+    fn update_desktops(&self) {
+        let names: Vec<u8> = self
+            .workspaces
+            .iter()
+            .flat_map(|workspace| workspace.name().bytes().chain([0]))
+            .collect();
+
+        self.set_property(
+            self.root,
+            self.atoms.net_number_of_desktops,
+            x::ATOM_CARDINAL,
+            &[self.workspaces.len() as u32],
+        );
+        self.set_property(
+            self.root,
+            self.atoms.net_desktop_names,
+            self.atoms.utf8_string,
+            &names,
+        );
+        self.set_property(
+            self.root,
+            self.atoms.net_desktop_geometry,
+            x::ATOM_CARDINAL,
+            &[
+                self.screen_rect.width as u32,
+                self.screen_rect.height as u32,
+            ],
+        );
+        self.set_property(
+            self.root,
+            self.atoms.net_desktop_viewport,
+            x::ATOM_CARDINAL,
+            &vec![0u32; 2 * self.workspaces.len()],
+        );
+
+        self.update_current_desktop();
+
+        for client in self.clients() {
+            self.update_client_desktop(client.window());
+        }
+    }
+
+    /// Publishes the workspace shown on the focused monitor in
+    /// `_NET_CURRENT_DESKTOP`.
+    fn update_current_desktop(&self) {
+        self.set_property(
+            self.root,
+            self.atoms.net_current_desktop,
+            x::ATOM_CARDINAL,
+            &[self.current_workspace() as u32],
+        );
+    }
+
+    /// Publishes the workspace the given window is on in `_NET_WM_DESKTOP`.
+    fn update_client_desktop(&self, window: x::Window) {
+        if let Some(index) = self.workspace_of(window) {
+            self.set_property(
+                window,
+                self.atoms.net_wm_desktop,
+                x::ATOM_CARDINAL,
+                &[index as u32],
+            );
+        }
+    }
+
+    /// Puts all windows back the way they were before they were managed,
+    /// showing the ones on hidden workspaces.
+    fn release_windows(&mut self) {
+        let clients: Vec<_> = self
+            .workspaces
+            .iter_mut()
+            .flat_map(|workspace| workspace.take_clients())
+            .collect();
+
+        for client in clients {
+            let window = client.window();
+            let (geometry, border_width) = client.original_geometry();
+
+            println!("Releasing window: {:?}", window);
+
+            self.conn.send_request(&x::ConfigureWindow {
+                window,
+                value_list: &[
+                    x::ConfigWindow::X(geometry.x as i32),
+                    x::ConfigWindow::Y(geometry.y as i32),
+                    x::ConfigWindow::Width(geometry.width.max(1) as u32),
+                    x::ConfigWindow::Height(geometry.height.max(1) as u32),
+                    x::ConfigWindow::BorderWidth(border_width as u32),
+                ],
+            });
+            self.conn.send_request(&x::MapWindow { window });
+            self.conn.send_request(&x::DeleteProperty {
+                window,
+                property: self.atoms.net_wm_desktop,
+            });
+        }
+    }
+
+    fn shutdown(&mut self) -> xcb::Result<()> {
+        self.ipc_server.flush_all();
+
+        // A window manager replacing this one binds to the socket as soon as
+        // the meta window, which owns the `WM_S<n>` selection, is destroyed.
+        self.ipc_server.close();
+
+        // Deleting properties that were never set on the root window, like
+        // the per-window ones, does no harm.
+        for property in ewmh::supported(&self.atoms) {
+            self.conn.send_request(&x::DeleteProperty {
+                window: self.root,
+                property,
+            });
+        }
+
+        // The new instance takes over the windows as they are.
+        if self.restart_state_path.is_none() {
+            self.release_windows();
+        }
+
+        self.conn.send_request(&x::DestroyWindow {
+            window: self.meta_window,
+        });
+
+        self.conn.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Turns the error of a request on a window that no longer exists into
+/// `None`, keeping other errors.
+fn unless_gone<T>(result: xcb::Result<T>) -> xcb::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(xcb::Error::Protocol(err)) => {
+            println!("X error: {:?}", err);
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn to_hex_color(color: Color) -> HexColor {
+    HexColor::new(color.r, color.g, color.b)
+}
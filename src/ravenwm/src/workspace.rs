@@ -0,0 +1,304 @@
+use xcb::x;
+
+use crate::client::XClient;
+use crate::geometry::Rectangle;
+use crate::layout::{self, Layout, LayoutState};
+
+/// A workspace with its own clients, layout and focus history.
+pub struct Workspace {
+    name: String,
+    clients: Vec<XClient>,
+    layouts: Vec<Box<dyn Layout<x::Window>>>,
+    layout_index: usize,
+    is_stacking: bool,
+    focus_history: Vec<x::Window>,
+}
+
+impl Workspace {
+    /// Creates a new, empty [`Workspace`] with the given name.
+    pub fn new(name: String, master_count: usize, master_ratio: f32) -> Self {
+        Self {
+            name,
+            clients: Vec::new(),
+            layouts: layout::all(master_count, master_ratio),
+            layout_index: 0,
+            is_stacking: false,
+            focus_history: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    /// Returns the clients on this workspace, in the order they were added.
+    pub fn clients(&self) -> &[XClient] {
+        &self.clients
+    }
+
+    /// Returns whether the given window is on this workspace.
+    pub fn contains(&self, window: x::Window) -> bool {
+        self.clients.iter().any(|client| client.window() == window)
+    }
+
+    /// Returns the client of the given window, if it is on this workspace.
+    pub fn client_mut(&mut self, window: x::Window) -> Option<&mut XClient> {
+        self.clients
+            .iter_mut()
+            .find(|client| client.window() == window)
+    }
+
+    /// Adds the given client to this workspace.
+    pub fn add(&mut self, client: XClient) {
+        self.clients.push(client);
+    }
+
+    /// Removes the client of the given window from this workspace.
+    pub fn remove(&mut self, window: x::Window) -> Option<XClient> {
+        self.focus_history.retain(|focused| *focused != window);
+
+        let index = self
+            .clients
+            .iter()
+            .position(|client| client.window() == window)?;
+
+        Some(self.clients.remove(index))
+    }
+
+    /// Removes all clients from this workspace.
+    pub fn take_clients(&mut self) -> Vec<XClient> {
+        self.focus_history.clear();
+
+        std::mem::take(&mut self.clients)
+    }
+
+    /// Remembers that the given window was focused, if it is on this
+    /// workspace.
+    pub fn focus(&mut self, window: x::Window) {
+        if self.contains(window) {
+            self.focus_history.retain(|focused| *focused != window);
+            self.focus_history.push(window);
+        }
+    }
+
+    /// Returns the windows that were focused, the most recent one last.
+    pub fn focus_history(&self) -> &[x::Window] {
+        &self.focus_history
+    }
+
+    /// Returns the window that was focused most recently, falling back to the
+    /// newest one.
+    pub fn last_focused(&self) -> Option<x::Window> {
+        self.focus_history
+            .last()
+            .copied()
+            .or_else(|| self.clients.last().map(|client| client.window()))
+    }
+
+    /// Returns whether the given window is on this workspace and floats.
+    pub fn is_floating(&self, window: x::Window) -> bool {
+        self.clients.iter().any(|client| {
+            client.window() == window
+                && !client.is_fullscreen()
+                && (self.is_stacking || client.is_floating())
+        })
+    }
+
+    /// Returns whether the given window is on this workspace and tiled.
+    pub fn is_tiled(&self, window: x::Window) -> bool {
+        self.contains(window) && !self.is_floating(window) && !self.is_fullscreen(window)
+    }
+
+    /// Returns whether the given window is on this workspace and fullscreen.
+    pub fn is_fullscreen(&self, window: x::Window) -> bool {
+        self.clients
+            .iter()
+            .any(|client| client.window() == window && client.is_fullscreen())
+    }
+
+    pub fn is_stacking(&self) -> bool {
+        self.is_stacking
+    }
+
+    /// Returns whether the tiled windows share their areas, e.g., in the
+    /// monocle layout.
+    pub fn is_overlapping(&self) -> bool {
+        !self.is_stacking && self.layouts[self.layout_index].overlaps()
+    }
+
+    /// Returns the current tiled layout, which is not used while stacking.
+    pub fn layout_mut(&mut self) -> &mut dyn Layout<x::Window> {
+        self.layouts[self.layout_index].as_mut()
+    }
+
+    /// Returns all of the tiled layouts.
+    pub fn layouts_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Layout<x::Window>>> {
+        self.layouts.iter_mut()
+    }
+
+    /// Returns the settings of the tiled layouts, to hand them over on
+    /// restart.
+    pub fn layout_state(&self) -> LayoutState<x::Window> {
+        let mut state = LayoutState::default();
+
+        for layout in &self.layouts {
+            layout.save(&mut state);
+        }
+
+        state
+    }
+
+    /// Restores the settings of the tiled layouts.
+    pub fn restore_layouts(&mut self, state: &LayoutState<x::Window>) {
+        for layout in &mut self.layouts {
+            layout.restore(state);
+        }
+    }
+
+    /// Returns the name of the current layout.
+    pub fn layout_name(&self) -> &'static str {
+        if self.is_stacking {
+            layout::STACKING
+        } else {
+            self.layouts[self.layout_index].name()
+        }
+    }
+
+    /// Switches to the layout with the given name.
+    pub fn set_layout(&mut self, name: &str) -> Result<(), String> {
+        if name == layout::STACKING {
+            self.is_stacking = true;
+        } else if name == layout::TILING {
+            self.is_stacking = false;
+        } else {
+            self.layout_index = self
+                .layouts
+                .iter()
+                .position(|layout| layout.name() == name)
+                .ok_or_else(|| format!("Unknown layout '{}'", name))?;
+            self.is_stacking = false;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the area of every client within the given area, with the given
+    /// gap between tiled clients.
+    ///
+    /// Floating clients get their floating area, and fullscreen clients get
+    /// the given fullscreen area.
+    pub fn arrange(
+        &mut self,
+        area: Rectangle,
+        fullscreen_area: Rectangle,
+        gap: u16,
+        focused: Option<x::Window>,
+    ) -> Vec<(x::Window, Rectangle)> {
+        let mut areas: Vec<_> = self
+            .clients
+            .iter()
+            .filter(|client| !client.is_fullscreen() && (self.is_stacking || client.is_floating()))
+            .map(|client| (client.window(), client.floating_rect()))
+            .collect();
+
+        if !self.is_stacking {
+            let windows: Vec<_> = self
+                .clients
+                .iter()
+                .filter(|client| !client.is_fullscreen() && !client.is_floating())
+                .map(|client| client.window())
+                .collect();
+
+            let rects = self.layouts[self.layout_index].arrange(area, &windows, focused, gap);
+
+            areas.extend(windows.into_iter().zip(rects));
+        }
+
+        areas.extend(
+            self.clients
+                .iter()
+                .filter(|client| client.is_fullscreen())
+                .map(|client| (client.window(), fullscreen_area)),
+        );
+
+        areas
+    }
+}
+
+/// Returns the index of the workspace the given window is on, if any.
+pub fn position_of(workspaces: &[Workspace], window: x::Window) -> Option<usize> {
+    workspaces
+        .iter()
+        .position(|workspace| workspace.contains(window))
+}
+
+#[cfg(test)]
+mod tests {
+    use xcb::XidNew;
+
+    use super::*;
+
+    fn window(id: u32) -> x::Window {
+        unsafe { x::Window::new(id) }
+    }
+
+    #[test]
+    fn focus_returns_to_previously_focused_window() {
+        let mut workspace = Workspace::new("1".into(), 1, 0.5);
+        let area = Rectangle::new(0, 0, 100, 100);
+
+        for id in 1..=3 {
+            workspace.add(XClient::new(window(id), area));
+        }
+
+        assert_eq!(workspace.last_focused(), Some(window(3)));
+
+        workspace.focus(window(1));
+        workspace.focus(window(2));
+        workspace.remove(window(2));
+
+        assert_eq!(workspace.last_focused(), Some(window(1)));
+    }
+
+    #[test]
+    fn only_monocle_overlaps() {
+        let mut workspace = Workspace::new("1".into(), 1, 0.5);
+        assert!(!workspace.is_overlapping());
+
+        workspace.set_layout("monocle").unwrap();
+        assert!(workspace.is_overlapping());
+
+        workspace.set_layout("stacking").unwrap();
+        assert!(!workspace.is_overlapping());
+    }
+
+    #[test]
+    fn floating_is_known_on_hidden_workspaces() {
+        let area = Rectangle::new(0, 0, 100, 100);
+        let mut workspaces = vec![
+            Workspace::new("1".into(), 1, 0.5),
+            Workspace::new("2".into(), 1, 0.5),
+        ];
+
+        workspaces[0].add(XClient::new(window(1), area));
+
+        let mut floating = XClient::new(window(2), area);
+        floating.set_floating(true);
+        workspaces[1].add(floating);
+        workspaces[1].add(XClient::new(window(3), area));
+
+        let is_floating = |window| {
+            position_of(&workspaces, window)
+                .is_some_and(|index| workspaces[index].is_floating(window))
+        };
+
+        assert!(!is_floating(window(1)));
+        assert!(is_floating(window(2)));
+        assert!(!is_floating(window(3)));
+        assert!(!is_floating(window(4)));
+    }
+}
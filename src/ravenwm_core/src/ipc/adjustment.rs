@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A new value for a setting, either absolute or relative to the current value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Adjustment<T> {
+    /// Set the setting to the given value.
+    Set(T),
+
+    /// Add the given value to the setting.
+    Change(T),
+}
+
+impl<T> Adjustment<T>
+where
+    T: Copy + std::ops::Add<Output = T>,
+{
+    /// Applies this adjustment to the given current value.
+    pub fn apply(self, current: T) -> T {
+        match self {
+            Self::Set(value) => value,
+            Self::Change(delta) => current + delta,
+        }
+    }
+}
+
+impl Adjustment<i32> {
+    /// Applies this adjustment to the given current value, saturating at the
+    /// bounds of `i32` instead of overflowing.
+    pub fn apply_saturating(self, current: i32) -> i32 {
+        match self {
+            Self::Set(value) => value,
+            Self::Change(delta) => current.saturating_add(delta),
+        }
+    }
+}
+
+impl Adjustment<f32> {
+    /// Returns whether the value is finite, i.e., neither infinite nor NaN.
+    pub fn is_finite(self) -> bool {
+        match self {
+            Self::Set(value) | Self::Change(value) => value.is_finite(),
+        }
+    }
+}
+
+impl<T: FromStr> FromStr for Adjustment<T> {
+    type Err = String;
+
+    /// Parses an adjustment, where a leading `+` or `-` makes it relative
+    /// (e.g., `+0.05`), and no sign makes it absolute (e.g., `0.6`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid value '{}'", s);
+
+        if let Some(delta) = s.strip_prefix('+') {
+            delta.parse().map(Self::Change).map_err(|_| invalid())
+        } else if s.starts_with('-') {
+            s.parse().map(Self::Change).map_err(|_| invalid())
+        } else {
+            s.parse().map(Self::Set).map_err(|_| invalid())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_adjustment() {
+        assert_eq!("0.6".parse(), Ok(Adjustment::Set(0.6)));
+        assert_eq!("+0.05".parse(), Ok(Adjustment::Change(0.05)));
+        assert_eq!("-1".parse(), Ok(Adjustment::Change(-1)));
+        assert!("+".parse::<Adjustment<i32>>().is_err());
+        assert!("x".parse::<Adjustment<f32>>().is_err());
+    }
+
+    #[test]
+    fn apply_adjustment() {
+        assert_eq!(Adjustment::Change(i32::MAX).apply_saturating(2), i32::MAX);
+        assert_eq!(Adjustment::Change(-3).apply_saturating(2), -1);
+        assert!(!"nan".parse::<Adjustment<f32>>().unwrap().is_finite());
+        assert!(Adjustment::Change(0.05).is_finite());
+    }
+}
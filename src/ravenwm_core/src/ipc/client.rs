@@ -0,0 +1,110 @@
+use std::io;
+use std::os::unix::net::UnixStream;
+
+use crate::ipc::{
+    read_frame, write_frame, Error, Event, EventKind, Handshake, Message, Reply, Result,
+    SocketPath, PROTOCOL_VERSION,
+};
+
+pub struct Client {
+    socket: UnixStream,
+
+    /// The [`Handshake`] received from the server.
+    server: Handshake,
+}
+
+impl Client {
+    pub fn connect(socket_path: &SocketPath) -> Result<Self> {
+        let mut socket = UnixStream::connect(socket_path.as_path()).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("Failed to connect to {}: {}", socket_path, err),
+            )
+        })?;
+
+        let server = Self::handshake(&mut socket)?;
+
+        Ok(Self { socket, server })
+    }
+
+    /// Returns the [`Handshake`] the server sent when we connected.
+    pub fn server(&self) -> &Handshake {
+        &self.server
+    }
+
+    /// Sends the given [`Message`] and waits for the [`Reply`].
+    pub fn send(&mut self, message: &Message) -> Result<Reply> {
+        self.check_supported(message)?;
+
+        write_frame(&mut self.socket, message)?;
+
+        self.receive()
+    }
+
+    /// Sends all of the given [`Message`]s and waits for their [`Reply`]s.
+    ///
+    /// The replies are returned in the same order as the messages.
+    pub fn send_batch(&mut self, messages: &[Message]) -> Result<Vec<Reply>> {
+        for message in messages {
+            self.check_supported(message)?;
+        }
+
+        for message in messages {
+            write_frame(&mut self.socket, message)?;
+        }
+
+        messages.iter().map(|_| self.receive()).collect()
+    }
+
+    /// Subscribes to the given kinds of [`Event`]s.
+    pub fn subscribe(mut self, events: Vec<EventKind>) -> Result<Subscription> {
+        match self.send(&Message::Subscribe { events })? {
+            Reply::Ok => Ok(Subscription {
+                socket: self.socket,
+            }),
+            Reply::Error { reason } => Err(Error::Rejected(reason)),
+            Reply::Data(_) => Err(Error::UnexpectedReply),
+        }
+    }
+
+    /// Exchanges [`Handshake`]s with the server and returns the server's.
+    fn handshake(socket: &mut UnixStream) -> Result<Handshake> {
+        write_frame(socket, &Handshake::new())?;
+
+        let server: Handshake = read_frame(socket)?.ok_or(Error::ConnectionClosed)?;
+
+        if !server.is_compatible() {
+            return Err(Error::VersionMismatch {
+                client: PROTOCOL_VERSION,
+                server: server.protocol_version,
+            });
+        }
+
+        Ok(server)
+    }
+
+    fn check_supported(&self, message: &Message) -> Result<()> {
+        if self.server.supports(message.command()) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedCommand(message.command()))
+        }
+    }
+
+    fn receive(&mut self) -> Result<Reply> {
+        read_frame(&mut self.socket)?.ok_or(Error::ConnectionClosed)
+    }
+}
+
+/// A stream of [`Event`]s from `ravenwm`.
+pub struct Subscription {
+    socket: UnixStream,
+}
+
+impl Iterator for Subscription {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_frame(&mut self.socket).transpose()
+    }
+}
@@ -0,0 +1,109 @@
+use std::{fmt, io};
+
+/// A specialized [`Result`](std::result::Result) type for IPC operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error that occurred while communicating over IPC.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error occurred on the socket.
+    Io(io::Error),
+
+    /// A value could not be serialized or deserialized.
+    Codec(bincode::Error),
+
+    /// A frame was larger than [`MAX_FRAME_LENGTH`](super::MAX_FRAME_LENGTH).
+    FrameTooLarge(u32),
+
+    /// A frame used an unsupported wire format version.
+    UnsupportedFrameVersion(u8),
+
+    /// The connection was closed in the middle of a frame.
+    TruncatedFrame,
+
+    /// The connection was closed before a reply was received.
+    ConnectionClosed,
+
+    /// The client and server speak different versions of the protocol.
+    VersionMismatch { client: u32, server: u32 },
+
+    /// The client did not start the connection with a
+    /// [`Handshake`](super::Handshake).
+    MissingHandshake,
+
+    /// The server does not support the given command.
+    UnsupportedCommand(&'static str),
+
+    /// `$DISPLAY` is not set, so the socket path cannot be determined.
+    MissingDisplay,
+
+    /// The X display name could not be parsed.
+    InvalidDisplay(String),
+
+    /// Another `ravenwm` instance is already listening on the socket.
+    AlreadyRunning(String),
+
+    /// `ravenwm` rejected the request.
+    Rejected(String),
+
+    /// `ravenwm` sent a reply that was not expected.
+    UnexpectedReply,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::Codec(err) => write!(f, "Malformed message: {}", err),
+            Self::FrameTooLarge(length) => write!(f, "Frame of {} bytes is too large", length),
+            Self::UnsupportedFrameVersion(version) => {
+                write!(f, "Unsupported frame version {}", version)
+            }
+            Self::TruncatedFrame => write!(f, "Connection closed in the middle of a frame"),
+            Self::ConnectionClosed => write!(f, "Connection closed before a reply was received"),
+            Self::VersionMismatch { client, server } => write!(
+                f,
+                "Protocol version mismatch: ravenc speaks version {} but ravenwm speaks version {}",
+                client, server
+            ),
+            Self::MissingHandshake => write!(
+                f,
+                "Expected a protocol handshake; the client is likely older than ravenwm"
+            ),
+            Self::UnsupportedCommand(command) => {
+                write!(f, "The running ravenwm does not support '{}'", command)
+            }
+            Self::MissingDisplay => write!(f, "DISPLAY is not set"),
+            Self::InvalidDisplay(display) => write!(f, "Invalid display name '{}'", display),
+            Self::AlreadyRunning(socket_path) => write!(
+                f,
+                "Another instance of ravenwm is already listening on {}",
+                socket_path
+            ),
+            Self::Rejected(reason) => write!(f, "{}", reason),
+            Self::UnexpectedReply => write!(f, "Unexpected reply"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Codec(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Self {
+        Self::Codec(err)
+    }
+}
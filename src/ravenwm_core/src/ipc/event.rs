@@ -0,0 +1,113 @@
+use std::fmt;
+use std::str::FromStr;
+
+use hex_color::HexColor;
+use serde::{Deserialize, Serialize};
+
+/// An event emitted by `ravenwm` to subscribed clients.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    /// A window was mapped.
+    WindowMapped { window: u32 },
+
+    /// A window was unmapped.
+    WindowUnmapped { window: u32 },
+
+    /// The focused window changed.
+    FocusChanged { window: Option<u32> },
+
+    /// The border settings changed.
+    BorderChanged { width: u32, color: HexColor },
+
+    /// The layout changed.
+    LayoutChanged { layout: String },
+
+    /// `ravenwm` is quitting.
+    Quit,
+
+    /// The configuration file was reloaded.
+    ///
+    /// Contains the reason if the file was invalid and nothing was applied.
+    ConfigReloaded { error: Option<String> },
+
+    /// Another workspace is shown.
+    WorkspaceChanged { workspace: u32, name: String },
+
+    /// Another monitor is focused.
+    MonitorFocused { monitor: u32, name: String },
+}
+
+impl Event {
+    /// Returns the [`EventKind`] of this event.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::WindowMapped { .. } | Self::WindowUnmapped { .. } => EventKind::Window,
+            Self::FocusChanged { .. } => EventKind::Focus,
+            Self::BorderChanged { .. } => EventKind::Border,
+            Self::LayoutChanged { .. } => EventKind::Layout,
+            Self::Quit => EventKind::Quit,
+            Self::ConfigReloaded { .. } => EventKind::Config,
+            Self::WorkspaceChanged { .. } => EventKind::Workspace,
+            Self::MonitorFocused { .. } => EventKind::Monitor,
+        }
+    }
+}
+
+/// A kind of [`Event`] that can be subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    Window,
+    Focus,
+    Border,
+    Layout,
+    Quit,
+    Config,
+    Workspace,
+    Monitor,
+}
+
+impl EventKind {
+    /// All of the [`EventKind`]s.
+    pub const ALL: [EventKind; 8] = [
+        EventKind::Window,
+        EventKind::Focus,
+        EventKind::Border,
+        EventKind::Layout,
+        EventKind::Quit,
+        EventKind::Config,
+        EventKind::Workspace,
+        EventKind::Monitor,
+    ];
+
+    /// Returns the name of this [`EventKind`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Window => "window",
+            Self::Focus => "focus",
+            Self::Border => "border",
+            Self::Layout => "layout",
+            Self::Quit => "quit",
+            Self::Config => "config",
+            Self::Workspace => "workspace",
+            Self::Monitor => "monitor",
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown event kind '{}'", s))
+    }
+}
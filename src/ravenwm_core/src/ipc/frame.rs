@@ -0,0 +1,195 @@
+use std::io::{self, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ipc::{Error, Result};
+
+/// The version of the frame wire format.
+pub const FRAME_VERSION: u8 = 1;
+
+/// The maximum length of a frame payload, in bytes.
+pub const MAX_FRAME_LENGTH: u32 = 1024 * 1024;
+
+/// The length of a frame header, in bytes.
+///
+/// A header consists of the payload length as a big-endian `u32`, followed by
+/// the [`FRAME_VERSION`] byte.
+const HEADER_LENGTH: usize = 5;
+
+/// Encodes the given value as a single frame.
+pub fn encode_frame<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let payload = bincode::serialize(value)?;
+
+    let length = u32::try_from(payload.len()).unwrap_or(u32::MAX);
+    if length > MAX_FRAME_LENGTH {
+        return Err(Error::FrameTooLarge(length));
+    }
+
+    let mut buffer = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    buffer.extend_from_slice(&length.to_be_bytes());
+    buffer.push(FRAME_VERSION);
+    buffer.extend_from_slice(&payload);
+
+    Ok(buffer)
+}
+
+/// Writes the given value to the writer as a single frame.
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    writer.write_all(&encode_frame(value)?)?;
+
+    Ok(())
+}
+
+/// Reads a single frame from the reader.
+///
+/// Returns `None` if the reader reached the end of the stream before a new
+/// frame started.
+pub fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>> {
+    let mut header = [0; HEADER_LENGTH];
+
+    let mut read = 0;
+    while read < HEADER_LENGTH {
+        match reader.read(&mut header[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(Error::TruncatedFrame),
+            Ok(count) => read += count,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let mut payload = vec![0; parse_header(&header)?];
+    reader.read_exact(&mut payload).map_err(|err| {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            Error::TruncatedFrame
+        } else {
+            err.into()
+        }
+    })?;
+
+    Ok(Some(bincode::deserialize(&payload)?))
+}
+
+/// Removes the first complete frame from the front of the buffer and returns
+/// its payload.
+///
+/// Returns `None` if the buffer does not contain a complete frame yet.
+pub(crate) fn take_frame(buffer: &mut Vec<u8>) -> Result<Option<Vec<u8>>> {
+    if buffer.len() < HEADER_LENGTH {
+        return Ok(None);
+    }
+
+    let mut header = [0; HEADER_LENGTH];
+    header.copy_from_slice(&buffer[..HEADER_LENGTH]);
+
+    let frame_length = HEADER_LENGTH + parse_header(&header)?;
+    if buffer.len() < frame_length {
+        return Ok(None);
+    }
+
+    let payload = buffer[HEADER_LENGTH..frame_length].to_vec();
+    buffer.drain(..frame_length);
+
+    Ok(Some(payload))
+}
+
+/// Validates a frame header and returns the length of the payload.
+fn parse_header(header: &[u8; HEADER_LENGTH]) -> Result<usize> {
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let version = header[4];
+
+    if version != FRAME_VERSION {
+        return Err(Error::UnsupportedFrameVersion(version));
+    }
+
+    if length > MAX_FRAME_LENGTH {
+        return Err(Error::FrameTooLarge(length));
+    }
+
+    Ok(length as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::ipc::Message;
+
+    #[test]
+    fn frame_round_trip() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Message::SetBorderWidth { width: 4 }).unwrap();
+        write_frame(&mut buffer, &Message::Quit).unwrap();
+
+        let mut reader = Cursor::new(buffer);
+
+        assert!(matches!(
+            read_frame(&mut reader).unwrap(),
+            Some(Message::SetBorderWidth { width: 4 })
+        ));
+        assert!(matches!(
+            read_frame(&mut reader).unwrap(),
+            Some(Message::Quit)
+        ));
+        assert!(read_frame::<_, Message>(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn frame_with_unsupported_version() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Message::Quit).unwrap();
+        buffer[4] = FRAME_VERSION + 1;
+
+        let err = read_frame::<_, Message>(&mut Cursor::new(buffer)).unwrap_err();
+
+        assert!(matches!(err, Error::UnsupportedFrameVersion(_)));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &Message::SetBorderWidth { width: 4 }).unwrap();
+        buffer.truncate(buffer.len() - 1);
+
+        let err = read_frame::<_, Message>(&mut Cursor::new(buffer)).unwrap_err();
+
+        assert!(matches!(err, Error::TruncatedFrame));
+    }
+
+    #[test]
+    fn take_frame_waits_for_complete_frame() {
+        let frame = encode_frame(&Message::SetBorderWidth { width: 4 }).unwrap();
+
+        let mut buffer = frame[..frame.len() - 1].to_vec();
+        assert!(take_frame(&mut buffer).unwrap().is_none());
+
+        buffer.push(frame[frame.len() - 1]);
+        buffer.extend_from_slice(&encode_frame(&Message::Quit).unwrap());
+
+        let payload = take_frame(&mut buffer).unwrap().unwrap();
+        assert!(matches!(
+            bincode::deserialize(&payload).unwrap(),
+            Message::SetBorderWidth { width: 4 }
+        ));
+
+        let payload = take_frame(&mut buffer).unwrap().unwrap();
+        assert!(matches!(
+            bincode::deserialize(&payload).unwrap(),
+            Message::Quit
+        ));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn take_frame_rejects_oversized_frame() {
+        let mut buffer = (MAX_FRAME_LENGTH + 1).to_be_bytes().to_vec();
+        buffer.push(FRAME_VERSION);
+
+        assert!(matches!(
+            take_frame(&mut buffer),
+            Err(Error::FrameTooLarge(_))
+        ));
+    }
+}
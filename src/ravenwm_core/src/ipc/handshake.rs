@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ipc::Message;
+
+/// The version of the IPC protocol.
+///
+/// [`Message`]s, [`Reply`](super::Reply)s and [`Event`](super::Event)s are
+/// encoded using the index of their enum variants, so this must be bumped
+/// whenever a variant is removed or reordered, or the fields of an existing
+/// variant change. New commands may be added to the end of [`Message`] without
+/// bumping the version, as clients check [`Handshake::supports`] before sending
+/// them. This does not hold for new [`Query`](super::Query),
+/// [`Event`](super::Event), [`EventKind`](super::EventKind) or
+/// [`Data`](super::Data) variants: older peers cannot ask whether they are
+/// supported and fail to decode them, so adding one also requires a bump.
+///
+/// Version 2 added the `workspaces` and `monitors` queries with their
+/// [`Data`](super::Data), the `workspace` and `monitor` events, `floating` and
+/// `workspace` to [`ClientInfo`](super::ClientInfo), and `monitor` to
+/// [`WorkspaceInfo`](super::WorkspaceInfo).
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The first frame exchanged on every connection.
+///
+/// The client sends its handshake first and the server answers with its own.
+/// The layout of this struct is part of the wire format and must never change,
+/// so that mismatched versions can always be reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handshake {
+    /// The [`PROTOCOL_VERSION`] of the sender.
+    pub protocol_version: u32,
+
+    /// The names of the commands the sender supports.
+    pub commands: Vec<String>,
+}
+
+impl Default for Handshake {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Handshake {
+    /// Creates a new [`Handshake`] for this version of the protocol.
+    pub fn new() -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            commands: Message::COMMANDS
+                .iter()
+                .map(|command| command.to_string())
+                .collect(),
+        }
+    }
+
+    /// Returns whether the sender supports the given command.
+    pub fn supports(&self, command: &str) -> bool {
+        self.commands.iter().any(|supported| supported == command)
+    }
+
+    /// Returns whether the sender speaks the same version of the protocol as us.
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+}
@@ -1,7 +1,12 @@
+use std::str::FromStr;
+
 use hex_color::HexColor;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::ipc::{Adjustment, EventKind};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Message {
     /// Quit `ravenwm`.
     Quit,
@@ -21,4 +26,261 @@ pub enum Message {
     SetBorderColor {
         color: HexColor,
     },
+
+    /// Subscribe to the given kinds of events.
+    ///
+    /// Subscribes to all events if no kinds are given.
+    Subscribe {
+        events: Vec<EventKind>,
+    },
+
+    /// Query the state of `ravenwm`.
+    Query(Query),
+
+    /// Bind the given key chord (e.g., `super + alt + w`) to an action.
+    ///
+    /// Replaces any action the chord was bound to before.
+    Bind {
+        chord: String,
+        action: Action,
+    },
+
+    /// Remove the binding of the given key chord.
+    Unbind {
+        chord: String,
+    },
+
+    /// Reload the configuration file.
+    Reload,
+
+    /// Set the share of the screen taken up by the master area.
+    SetMasterRatio {
+        ratio: Adjustment<f32>,
+    },
+
+    /// Set the number of windows in the master area.
+    SetMasterCount {
+        count: Adjustment<i32>,
+    },
+
+    /// Change the split tree of the BSP layout.
+    Bsp(BspCommand),
+
+    /// Switch to the layout with the given name.
+    SetLayout {
+        name: String,
+    },
+
+    /// Toggle whether the focused window floats above the tiled windows.
+    ToggleFloating,
+
+    /// Show the workspace with the given number, counting from 1.
+    Workspace {
+        number: u32,
+    },
+
+    /// Move the focused window to the workspace with the given number,
+    /// counting from 1.
+    SendTo {
+        number: u32,
+    },
+
+    /// Focus the monitor with the given number, counting from 1 on the left.
+    FocusMonitor {
+        number: u32,
+    },
+
+    /// Move the focused window to the workspace shown on the monitor with the
+    /// given number, counting from 1 on the left.
+    SendToMonitor {
+        number: u32,
+    },
+
+    /// Close the active window by killing its client, without asking it
+    /// first.
+    KillWindow,
+
+    /// Restart `ravenwm` in place, keeping the windows and workspaces.
+    Restart,
+}
+
+impl Message {
+    /// The names of all of the commands.
+    pub const COMMANDS: &'static [&'static str] = &[
+        "quit",
+        "close_window",
+        "move_window",
+        "set_border_width",
+        "set_border_color",
+        "subscribe",
+        "query",
+        "bind",
+        "unbind",
+        "reload",
+        "set_master_ratio",
+        "set_master_count",
+        "bsp",
+        "set_layout",
+        "toggle_floating",
+        "workspace",
+        "send_to",
+        "focus_monitor",
+        "send_to_monitor",
+        "kill_window",
+        "restart",
+    ];
+
+    /// Returns the name of the command this message invokes.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::CloseWindow => "close_window",
+            Self::MoveWindow { .. } => "move_window",
+            Self::SetBorderWidth { .. } => "set_border_width",
+            Self::SetBorderColor { .. } => "set_border_color",
+            Self::Subscribe { .. } => "subscribe",
+            Self::Query(_) => "query",
+            Self::Bind { .. } => "bind",
+            Self::Unbind { .. } => "unbind",
+            Self::Reload => "reload",
+            Self::SetMasterRatio { .. } => "set_master_ratio",
+            Self::SetMasterCount { .. } => "set_master_count",
+            Self::Bsp(_) => "bsp",
+            Self::SetLayout { .. } => "set_layout",
+            Self::ToggleFloating => "toggle_floating",
+            Self::Workspace { .. } => "workspace",
+            Self::SendTo { .. } => "send_to",
+            Self::FocusMonitor { .. } => "focus_monitor",
+            Self::SendToMonitor { .. } => "send_to_monitor",
+            Self::KillWindow => "kill_window",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+/// A query for the state of `ravenwm`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Query {
+    /// All of the managed clients.
+    Clients,
+
+    /// The focused client.
+    Focused,
+
+    /// The current layout.
+    Layout,
+
+    /// The current configuration.
+    Config,
+
+    /// All of the workspaces.
+    Workspaces,
+
+    /// All of the monitors.
+    Monitors,
+}
+
+/// A change to the split tree of the BSP layout.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BspCommand {
+    /// Rotate the split containing the focused window clockwise by 90, 180 or
+    /// 270 degrees.
+    Rotate { degrees: u16 },
+
+    /// Mirror the split containing the focused window.
+    Flip(Flip),
+
+    /// Give every window the same area.
+    Balance,
+
+    /// Set the share of the split containing the focused window that is taken
+    /// up by its first half.
+    SetRatio(Adjustment<f32>),
+}
+
+/// A direction to mirror in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flip {
+    /// Swap left and right.
+    Horizontal,
+
+    /// Swap top and bottom.
+    Vertical,
+}
+
+impl FromStr for Flip {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            _ => Err(format!("Unknown flip direction '{}'", s)),
+        }
+    }
+}
+
+/// What `ravenwm` does when a bound key chord is pressed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Handle the given message as if it had been sent over IPC.
+    Message(Box<Message>),
+
+    /// Run the given shell command.
+    Spawn(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_listed() {
+        let messages = [
+            Message::Quit,
+            Message::CloseWindow,
+            Message::MoveWindow { x: 0, y: 0 },
+            Message::SetBorderWidth { width: 0 },
+            Message::SetBorderColor {
+                color: HexColor::new(0, 0, 0),
+            },
+            Message::Subscribe { events: Vec::new() },
+            Message::Query(Query::Clients),
+            Message::Bind {
+                chord: "super + w".into(),
+                action: Action::Spawn("xterm".into()),
+            },
+            Message::Unbind {
+                chord: "super + w".into(),
+            },
+            Message::Reload,
+            Message::SetMasterRatio {
+                ratio: Adjustment::Set(0.5),
+            },
+            Message::SetMasterCount {
+                count: Adjustment::Change(1),
+            },
+            Message::Bsp(BspCommand::Balance),
+            Message::SetLayout {
+                name: "grid".into(),
+            },
+            Message::ToggleFloating,
+            Message::Workspace { number: 2 },
+            Message::SendTo { number: 3 },
+            Message::FocusMonitor { number: 2 },
+            Message::SendToMonitor { number: 1 },
+            Message::KillWindow,
+            Message::Restart,
+        ];
+
+        for message in &messages {
+            assert!(Message::COMMANDS.contains(&message.command()));
+        }
+
+        assert_eq!(messages.len(), Message::COMMANDS.len());
+    }
 }
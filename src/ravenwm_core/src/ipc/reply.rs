@@ -0,0 +1,143 @@
+use hex_color::HexColor;
+use serde::{Deserialize, Serialize};
+
+/// A reply from `ravenwm` to a [`Message`](super::Message).
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Reply {
+    /// The message was handled successfully.
+    Ok,
+
+    /// The message could not be handled.
+    Error {
+        /// The reason the message could not be handled.
+        reason: String,
+    },
+
+    /// The message was handled successfully and produced some data.
+    Data(Data),
+}
+
+impl Reply {
+    /// Creates a new [`Reply::Error`] with the given reason.
+    pub fn error(reason: impl Into<String>) -> Self {
+        Self::Error {
+            reason: reason.into(),
+        }
+    }
+}
+
+/// The data payload of a [`Reply`].
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Data {
+    /// The managed clients, in the order they were mapped.
+    Clients(Vec<ClientInfo>),
+
+    /// The focused client, if any.
+    Focused(Option<ClientInfo>),
+
+    /// The current layout.
+    Layout { name: String },
+
+    /// The current configuration.
+    Config(ConfigInfo),
+
+    /// The workspaces, in order.
+    Workspaces(Vec<WorkspaceInfo>),
+
+    /// The monitors, from left to right.
+    Monitors(Vec<MonitorInfo>),
+}
+
+/// Information about a managed client.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientInfo {
+    /// The ID of the client's window.
+    pub window: u32,
+
+    /// The geometry of the client's window.
+    pub geometry: Geometry,
+
+    /// The class of the client, from `WM_CLASS`.
+    pub class: Option<String>,
+
+    /// The instance of the client, from `WM_CLASS`.
+    pub instance: Option<String>,
+
+    /// The title of the client, from `_NET_WM_NAME` or `WM_NAME`.
+    pub title: Option<String>,
+
+    /// Whether the client is focused.
+    pub focused: bool,
+
+    /// Whether the client floats instead of being tiled.
+    pub floating: bool,
+
+    /// The number of the workspace the client is on, if it is managed.
+    pub workspace: Option<u32>,
+}
+
+/// Information about a workspace.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    /// The number of the workspace, counting from 1.
+    pub number: u32,
+
+    /// The name of the workspace.
+    pub name: String,
+
+    /// The name of the layout of the workspace.
+    pub layout: String,
+
+    /// The number of clients on the workspace.
+    pub clients: u32,
+
+    /// Whether the workspace is shown on the focused monitor.
+    pub focused: bool,
+
+    /// The number of the monitor the workspace is shown on, if it is shown.
+    pub monitor: Option<u32>,
+}
+
+/// Information about a monitor.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    /// The number of the monitor, counting from 1 on the left.
+    pub number: u32,
+
+    /// The name of the monitor, from RandR.
+    pub name: String,
+
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+
+    /// The number of the workspace shown on the monitor.
+    pub workspace: u32,
+
+    /// Whether the monitor is focused.
+    pub focused: bool,
+}
+
+/// The geometry of a window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Geometry {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+}
+
+/// Information about the current configuration.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigInfo {
+    /// The width of window borders, in pixels.
+    pub border_width: u32,
+
+    /// The color of window borders.
+    pub border_color: HexColor,
+
+    /// The width of the gap around windows, in pixels.
+    pub gap_width: u32,
+}
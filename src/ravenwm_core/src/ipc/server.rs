@@ -0,0 +1,445 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::prelude::{AsRawFd, RawFd};
+
+use serde::Serialize;
+
+use crate::ipc::{
+    encode_frame, take_frame, Error, Event, EventKind, Handshake, Message, Reply, Result,
+    SocketPath, MAX_FRAME_LENGTH,
+};
+
+/// The maximum number of bytes that may be waiting to be sent to a connection
+/// before it is closed.
+const MAX_PENDING_WRITE_LENGTH: usize = 4 * MAX_FRAME_LENGTH as usize;
+
+/// A non-blocking IPC server.
+///
+/// None of the methods on the server block, so a slow or misbehaving client
+/// cannot stall the caller. Any data that cannot be sent right away is kept
+/// until the connection becomes writable and [`Server::flush`] is called.
+pub struct Server {
+    listener: UnixListener,
+    socket_path: SocketPath,
+    connections: Vec<Connection>,
+}
+
+impl Server {
+    pub fn bind(socket_path: &SocketPath) -> Result<Self> {
+        socket_path.remove_stale()?;
+
+        let listener = UnixListener::bind(socket_path.as_path()).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("Failed to bind to {}: {}", socket_path, err),
+            )
+        })?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            socket_path: socket_path.clone(),
+            connections: Vec::new(),
+        })
+    }
+
+    /// Removes the socket, so that another instance can bind to it right away.
+    ///
+    /// Connections that are already open are kept.
+    pub fn close(&mut self) {
+        match fs::remove_file(self.socket_path.as_path()) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => println!("Failed to remove {}: {}", self.socket_path, err),
+        }
+    }
+
+    /// Accepts all pending connections.
+    pub fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((socket, _)) => match socket.set_nonblocking(true) {
+                    Ok(()) => self.connections.push(Connection::new(socket)),
+                    Err(err) => println!("Failed to set up connection: {}", err),
+                },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    println!("Failed to accept connection: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Returns the file descriptors of all open connections.
+    pub fn connection_fds(&self) -> impl Iterator<Item = RawFd> + '_ {
+        self.connections.iter().map(|connection| connection.fd())
+    }
+
+    /// Returns the file descriptors of all connections that have data waiting
+    /// to be sent.
+    pub fn pending_write_fds(&self) -> impl Iterator<Item = RawFd> + '_ {
+        self.connections
+            .iter()
+            .filter(|connection| !connection.write_buffer.is_empty())
+            .map(|connection| connection.fd())
+    }
+
+    /// Receives all of the complete [`Request`]s that are available on the
+    /// connection with the given file descriptor.
+    ///
+    /// Malformed messages are reported back to the client as a
+    /// [`Reply::Error`]. The connection is closed if the client hung up or its
+    /// stream of frames can no longer be understood.
+    pub fn receive(&mut self, connection_fd: RawFd) -> Vec<Request> {
+        let Some(index) = self.connection_index(connection_fd) else {
+            return Vec::new();
+        };
+
+        let connection = &mut self.connections[index];
+
+        if let Err(err) = connection.read() {
+            println!("Failed to read from connection {}: {}", connection_fd, err);
+            connection.is_closing = true;
+        }
+
+        let mut requests = Vec::new();
+
+        loop {
+            match take_frame(&mut connection.read_buffer) {
+                Ok(Some(payload)) if !connection.is_handshaken => {
+                    connection.handshake(&payload);
+                    if connection.is_closing {
+                        connection.read_buffer.clear();
+                        break;
+                    }
+                }
+                Ok(Some(payload)) => match bincode::deserialize::<Message>(&payload) {
+                    Ok(message) => {
+                        connection.pending_requests += 1;
+                        requests.push(Request {
+                            connection_fd,
+                            message,
+                        });
+                    }
+                    Err(err) => connection.reject(Error::from(err)),
+                },
+                Ok(None) => break,
+                Err(err) => {
+                    // The frame boundaries are lost, so nothing else on this
+                    // connection can be understood.
+                    connection.reject(err);
+                    connection.read_buffer.clear();
+                    connection.is_closing = true;
+                    break;
+                }
+            }
+        }
+
+        if connection.is_closing && !connection.read_buffer.is_empty() {
+            println!(
+                "Connection {} closed: {}",
+                connection_fd,
+                Error::TruncatedFrame
+            );
+            connection.read_buffer.clear();
+        }
+
+        self.remove_finished_connections();
+
+        requests
+    }
+
+    /// Sends the given [`Reply`] to the connection with the given file
+    /// descriptor.
+    pub fn reply(&mut self, connection_fd: RawFd, reply: &Reply) {
+        if let Some(index) = self.connection_index(connection_fd) {
+            let connection = &mut self.connections[index];
+            connection.pending_requests = connection.pending_requests.saturating_sub(1);
+            connection.send(reply);
+        }
+
+        self.remove_finished_connections();
+    }
+
+    /// Sends as much of the pending data as possible to the connection with the
+    /// given file descriptor.
+    pub fn flush(&mut self, connection_fd: RawFd) {
+        if let Some(index) = self.connection_index(connection_fd) {
+            self.connections[index].flush();
+        }
+
+        self.remove_finished_connections();
+    }
+
+    /// Sends as much of the pending data as possible to all connections.
+    pub fn flush_all(&mut self) {
+        for connection in &mut self.connections {
+            connection.flush();
+        }
+
+        self.remove_finished_connections();
+    }
+
+    /// Subscribes the connection with the given file descriptor to the given
+    /// kinds of [`Event`]s.
+    ///
+    /// Subscribes to all events if no kinds are given.
+    pub fn subscribe(&mut self, connection_fd: RawFd, events: &[EventKind]) {
+        if let Some(index) = self.connection_index(connection_fd) {
+            let events = if events.is_empty() {
+                EventKind::ALL.to_vec()
+            } else {
+                events.to_vec()
+            };
+
+            self.connections[index].subscriptions = Some(events);
+        }
+    }
+
+    /// Sends the given [`Event`] to all connections subscribed to it.
+    pub fn broadcast(&mut self, event: &Event) {
+        let kind = event.kind();
+
+        for connection in &mut self.connections {
+            let is_subscribed = connection
+                .subscriptions
+                .as_ref()
+                .is_some_and(|subscriptions| subscriptions.contains(&kind));
+
+            if is_subscribed {
+                connection.send(event);
+            }
+        }
+
+        self.remove_finished_connections();
+    }
+
+    /// Closes the connection with the given file descriptor, e.g., after it
+    /// turned out to be invalid.
+    pub fn disconnect(&mut self, connection_fd: RawFd) {
+        self.connections
+            .retain(|connection| connection.fd() != connection_fd);
+    }
+
+    fn connection_index(&self, connection_fd: RawFd) -> Option<usize> {
+        self.connections
+            .iter()
+            .position(|connection| connection.fd() == connection_fd)
+    }
+
+    fn remove_finished_connections(&mut self) {
+        self.connections
+            .retain(|connection| !connection.is_finished());
+    }
+}
+
+impl AsRawFd for Server {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+/// A connection to the [`Server`].
+struct Connection {
+    socket: UnixStream,
+
+    /// The data received from the client that has not been decoded yet.
+    read_buffer: Vec<u8>,
+
+    /// The data waiting to be sent to the client.
+    write_buffer: Vec<u8>,
+
+    /// The kinds of [`Event`]s this connection is subscribed to, if any.
+    subscriptions: Option<Vec<EventKind>>,
+
+    /// The number of received requests that have not been replied to yet.
+    pending_requests: usize,
+
+    /// Whether the client has completed the [`Handshake`].
+    is_handshaken: bool,
+
+    /// Whether the connection should be closed once all pending data is sent.
+    is_closing: bool,
+
+    /// Whether the connection can no longer be used and should be closed
+    /// immediately.
+    is_broken: bool,
+}
+
+impl Connection {
+    fn new(socket: UnixStream) -> Self {
+        Self {
+            socket,
+            read_buffer: Vec::new(),
+            write_buffer: Vec::new(),
+            subscriptions: None,
+            pending_requests: 0,
+            is_handshaken: false,
+            is_closing: false,
+            is_broken: false,
+        }
+    }
+
+    fn fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+
+    /// Returns whether the connection should be closed.
+    fn is_finished(&self) -> bool {
+        self.is_broken
+            || (self.is_closing && self.pending_requests == 0 && self.write_buffer.is_empty())
+    }
+
+    /// Reads all of the data that is currently available.
+    ///
+    /// Marks the connection as closing if the client hung up.
+    fn read(&mut self) -> Result<()> {
+        let mut buffer = [0; 4096];
+
+        loop {
+            match self.socket.read(&mut buffer) {
+                Ok(0) => {
+                    self.is_closing = true;
+                    return Ok(());
+                }
+                Ok(count) => {
+                    self.read_buffer.extend_from_slice(&buffer[..count]);
+
+                    let max_length = MAX_FRAME_LENGTH as usize + buffer.len();
+                    if self.read_buffer.len() > max_length {
+                        // Leave the rest in the socket until the buffered
+                        // frames have been decoded.
+                        return Ok(());
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Answers the client's [`Handshake`].
+    ///
+    /// The connection is closed if the client speaks a different version of
+    /// the protocol, after telling it which version we speak.
+    fn handshake(&mut self, payload: &[u8]) {
+        let client = match bincode::deserialize::<Handshake>(payload) {
+            Ok(client) => client,
+            Err(_) => {
+                self.reject(Error::MissingHandshake);
+                self.is_closing = true;
+                return;
+            }
+        };
+
+        self.send(&Handshake::new());
+
+        if client.is_compatible() {
+            self.is_handshaken = true;
+        } else {
+            println!(
+                "Connection {} closed: {}",
+                self.fd(),
+                Error::VersionMismatch {
+                    client: client.protocol_version,
+                    server: Handshake::new().protocol_version,
+                }
+            );
+            self.is_closing = true;
+        }
+    }
+
+    /// Logs the given error and reports it back to the client.
+    fn reject(&mut self, err: Error) {
+        println!("Rejected message on connection {}: {}", self.fd(), err);
+
+        self.send(&Reply::error(err.to_string()));
+    }
+
+    /// Queues the given value to be sent and sends as much as possible.
+    fn send<T: Serialize>(&mut self, value: &T) {
+        match encode_frame(value) {
+            Ok(frame) => {
+                if self.write_buffer.len() + frame.len() > MAX_PENDING_WRITE_LENGTH {
+                    println!("Connection {} is not reading, closing it", self.fd());
+                    self.is_broken = true;
+                    return;
+                }
+
+                self.write_buffer.extend_from_slice(&frame);
+                self.flush();
+            }
+            Err(err) => println!("Failed to encode frame: {}", err),
+        }
+    }
+
+    /// Sends as much of the pending data as possible.
+    fn flush(&mut self) {
+        while !self.write_buffer.is_empty() {
+            match self.socket.write(&self.write_buffer) {
+                Ok(0) => {
+                    self.is_broken = true;
+                    return;
+                }
+                Ok(count) => {
+                    self.write_buffer.drain(..count);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    println!("Failed to write to connection {}: {}", self.fd(), err);
+                    self.is_broken = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// A [`Message`] received by the [`Server`] that is awaiting a [`Reply`].
+#[derive(Debug)]
+pub struct Request {
+    connection_fd: RawFd,
+    message: Message,
+}
+
+impl Request {
+    /// Returns the file descriptor of the connection this request was received on.
+    pub fn connection_fd(&self) -> RawFd {
+        self.connection_fd
+    }
+
+    /// Returns the [`Message`] sent with this request.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn closed_server_lets_another_bind() {
+        let path = env::temp_dir().join(format!("ravenwm-close-{}.sock", std::process::id()));
+        let socket_path = SocketPath::from(path.clone());
+
+        let mut server = Server::bind(&socket_path).unwrap();
+
+        assert!(matches!(
+            Server::bind(&socket_path),
+            Err(Error::AlreadyRunning(_))
+        ));
+
+        server.close();
+        let _new_server = Server::bind(&socket_path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+    }
+}
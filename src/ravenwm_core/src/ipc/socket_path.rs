@@ -0,0 +1,165 @@
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs, io};
+
+use crate::ipc::{Error, Result};
+
+/// The path to the socket of a `ravenwm` instance.
+///
+/// Every X display gets its own socket, so that multiple instances (e.g., one
+/// nested in Xephyr) can run side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketPath(PathBuf);
+
+impl SocketPath {
+    /// Returns the socket path for the current environment.
+    ///
+    /// Uses `$RAVENWM_SOCKET` if it is set, otherwise the socket for `$DISPLAY`.
+    pub fn new() -> Result<Self> {
+        if let Some(socket_path) = env::var_os("RAVENWM_SOCKET") {
+            return Ok(Self(socket_path.into()));
+        }
+
+        let display = env::var("DISPLAY").map_err(|_| Error::MissingDisplay)?;
+
+        Self::for_display(&display)
+    }
+
+    /// Returns the socket path for the given X display name (e.g., `:1`).
+    ///
+    /// The socket lives in `$XDG_RUNTIME_DIR`, falling back to the temporary
+    /// directory if it is not set.
+    pub fn for_display(display: &str) -> Result<Self> {
+        let display = DisplayName::parse(display)?;
+
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir);
+
+        Ok(Self(runtime_dir.join(display.socket_name())))
+    }
+
+    /// Returns the path to the socket.
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Removes the socket if it was left behind by an instance that is no
+    /// longer running.
+    ///
+    /// Returns [`Error::AlreadyRunning`] if an instance is still listening on it.
+    /// Any other failure to connect (e.g., for lack of permission) is returned
+    /// as well, leaving the socket alone.
+    pub(crate) fn remove_stale(&self) -> Result<()> {
+        match UnixStream::connect(&self.0) {
+            Ok(_) => Err(Error::AlreadyRunning(self.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                match fs::remove_file(&self.0) {
+                    Ok(()) => Ok(()),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            Err(err) => {
+                Err(io::Error::new(err.kind(), format!("Failed to check {}: {}", self, err)).into())
+            }
+        }
+    }
+}
+
+impl From<PathBuf> for SocketPath {
+    fn from(path: PathBuf) -> Self {
+        Self(path)
+    }
+}
+
+impl fmt::Display for SocketPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// An X display name, in the form `[host]:display[.screen]`.
+#[derive(Debug, PartialEq, Eq)]
+struct DisplayName {
+    host: String,
+    display: u32,
+    screen: u32,
+}
+
+impl DisplayName {
+    fn parse(name: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDisplay(name.to_string());
+
+        let (host, rest) = name.rsplit_once(':').ok_or_else(invalid)?;
+        let (display, screen) = rest.split_once('.').unwrap_or((rest, "0"));
+
+        Ok(Self {
+            host: host.to_string(),
+            display: display.parse().map_err(|_| invalid())?,
+            screen: screen.parse().map_err(|_| invalid())?,
+        })
+    }
+
+    /// Returns the file name of the socket for this display.
+    fn socket_name(&self) -> String {
+        format!(
+            "ravenwm_{}_{}_{}.sock",
+            self.host.replace('/', "_"),
+            self.display,
+            self.screen
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_local_display() {
+        let display = DisplayName::parse(":1").unwrap();
+
+        assert_eq!(
+            display,
+            DisplayName {
+                host: "".into(),
+                display: 1,
+                screen: 0
+            }
+        );
+        assert_eq!(display.socket_name(), "ravenwm__1_0.sock");
+    }
+
+    #[test]
+    fn parse_remote_display_with_screen() {
+        let display = DisplayName::parse("localhost:10.2").unwrap();
+
+        assert_eq!(
+            display,
+            DisplayName {
+                host: "localhost".into(),
+                display: 10,
+                screen: 2
+            }
+        );
+        assert_eq!(display.socket_name(), "ravenwm_localhost_10_2.sock");
+    }
+
+    #[test]
+    fn parse_invalid_display() {
+        assert!(DisplayName::parse("").is_err());
+        assert!(DisplayName::parse("localhost").is_err());
+        assert!(DisplayName::parse(":abc").is_err());
+        assert!(DisplayName::parse(":0.x").is_err());
+    }
+
+    #[test]
+    fn different_displays_get_different_sockets() {
+        let real = SocketPath::for_display(":0").unwrap();
+        let nested = SocketPath::for_display(":1").unwrap();
+
+        assert_ne!(real, nested);
+    }
+}